@@ -6,14 +6,794 @@
 //! forwards unrecognized parameters to the init process.
 
 use alloc::{
-    collections::BTreeMap,
+    borrow::Cow,
+    collections::{BTreeMap, btree_map::Entry},
     ffi::CString,
+    format,
     string::{String, ToString},
     vec::Vec,
 };
+use core::{cmp::Reverse, fmt, ops::Range};
 
 use component::{ComponentInitError, init_component};
-use spin::Once;
+use ostd::boot::cmdline::{split_arg, split_arg_spans};
+use spin::{Mutex, Once};
+
+use crate::{
+    parse::{ParamError, ParamNotes, ParseFlag, ParseParamValue},
+    types::{CommaList, NumericBase},
+};
+
+/// The name of the flag that makes the rest of the command line parsed strictly.
+///
+/// In strict mode, unknown parameters are reported as errors instead of being
+/// forwarded to the init process. Since the flag affects how the subsequent
+/// tokens are parsed, it is handled inline by the dispatcher rather than
+/// registered as an ordinary parameter.
+const STRICT_PARAM_NAME: &str = "cmdline_strict";
+
+/// The name of the parameter that lists the parameters whose setup is skipped.
+///
+/// The value is a comma-separated list of parameter names, e.g.
+/// `cmdline_disable=loglevel,console`. The listed parameters are still
+/// recognized, but their handlers are not called. Since the setup is done
+/// after all the tokens are grouped, the parameter takes effect wherever it
+/// appears before `--`, and it is handled inline by the dispatcher.
+const DISABLE_PARAM_NAME: &str = "cmdline_disable";
+
+/// The name of the parameter that limits the number of init arguments after `--`.
+///
+/// The excess arguments are dropped with a warning. The parameter must appear
+/// before `--` to take effect, so it is also handled inline by the dispatcher.
+const MAX_INIT_ARGS_PARAM_NAME: &str = "max_init_args";
+
+/// The name of the flag that expands environment variable references in init arguments.
+///
+/// The expansion is off by default. If enabled, each reference `$VAR` or
+/// `${VAR}` in the init arguments is substituted with the value of `VAR` in the
+/// init environment, and a reference to an unset `VAR` is kept as is. Since the
+/// expansion is done after all the tokens are parsed, the flag is handled
+/// inline by the dispatcher.
+const EXPAND_ENV_PARAM_NAME: &str = "init_expand_env";
+
+/// The name of the flag that logs the arguments passed to the init process.
+///
+/// Since the arguments are complete only after all the tokens are parsed, the
+/// flag is handled inline by the dispatcher.
+const DUMP_INIT_PARAM_NAME: &str = "dump_init";
+
+/// The name of the flag that requires all the init arguments to follow `--`.
+///
+/// With the flag, unknown tokens before `--` are reported as errors instead of
+/// being forwarded to the init process. Unlike [`STRICT_PARAM_NAME`], the flag
+/// applies to the whole command line wherever it appears, so the dispatcher
+/// looks it up before grouping any token.
+const STRICT_INIT_PARAM_NAME: &str = "strict_init";
+
+/// The name of the parameter that sets the path of the init executable.
+///
+/// The kernel passes the path as `argv[0]` of the init process, followed by
+/// the init arguments. See [`KernelCmdline::initproc_path`].
+const INIT_PATH_PARAM_NAME: &str = "init";
+
+/// The name of the parameter that sets the verbosity of the parser's diagnostics.
+///
+/// The levels are:
+/// - 0: The diagnostics are neither recorded nor logged.
+/// - 1: The diagnostics are recorded, but not logged.
+/// - 2: The diagnostics are recorded and logged as warnings. This is the default.
+/// - 3: The routing decisions of the tokens are also recorded and logged.
+///
+/// Since the level affects the parser itself, the dispatcher looks it up
+/// before grouping any token, like [`STRICT_INIT_PARAM_NAME`].
+const LOG_LEVEL_PARAM_NAME: &str = "cmdline_loglevel";
+
+/// The default level of [`LOG_LEVEL_PARAM_NAME`].
+const DEFAULT_LOG_LEVEL: u8 = 2;
+
+/// The maximum level of [`LOG_LEVEL_PARAM_NAME`].
+const MAX_LOG_LEVEL: u8 = 3;
+
+/// The name of the parameter that sets the base of the unprefixed numbers of
+/// the [`crate::types::HexInt`] parameters.
+///
+/// The value is `dec`, which is the default, or `hex`. The parameter applies to
+/// all the [`crate::types::HexInt`] parameters of the command line wherever it
+/// appears, so the dispatcher looks it up before grouping any token, like
+/// [`LOG_LEVEL_PARAM_NAME`]. It does not carry over to another command line,
+/// e.g., a fragment parsed with [`KernelCmdline::extend_from`].
+///
+/// Integer parameters that are parsed via [`core::str::FromStr`] (e.g. a plain
+/// `u32`) are not affected. Note that with `numeric_base=hex`, a decimal value
+/// can no longer be written for a [`crate::types::HexInt`] parameter, and a
+/// value like `10` is read as 16. Use it only if all such parameters are meant
+/// to be hexadecimal.
+const NUMERIC_BASE_PARAM_NAME: &str = "numeric_base";
+
+// The path belongs to the parsed command line, so it is stored in the
+// `KernelCmdline` being built rather than in a static storage.
+crate::submit! {
+    KernelParam::new_builtin(INIT_PATH_PARAM_NAME, setup_init_path)
+        .at_location(concat!(file!(), ":", line!()))
+        .with_allow_empty(false)
+        .with_help("Sets the path of the init executable")
+}
+
+fn setup_init_path(
+    cmdline: &mut KernelCmdline,
+    occurrences: &[Option<&str>],
+) -> Result<(), ParamError> {
+    let Some(last) = occurrences.last() else {
+        return Ok(());
+    };
+    let path = last.ok_or(ParamError::MissingValue)?;
+    cmdline.initproc_path = Some(path.to_string());
+    Ok(())
+}
+
+/// The name of the flag that decides whether unknown `key=value` tokens go to `envp`.
+///
+/// The flag is on by default. With `init_env=off`, such tokens are passed to
+/// the init process as `argv` verbatim, in their order among the bare tokens.
+/// Like [`STRICT_INIT_PARAM_NAME`], the flag applies to the whole command line.
+const INIT_ENV_PARAM_NAME: &str = "init_env";
+
+/// The kernel command line after its parameters have been dispatched.
+#[derive(Debug)]
+pub struct KernelCmdline {
+    cmdline: String,
+    initproc_path: Option<String>,
+    init_proc_args: InitprocArgs,
+    diagnostics: Vec<String>,
+    stats: CmdlineStats,
+    params: Vec<&'static KernelParam>,
+    effective_params: Vec<(String, Option<String>)>,
+    dropped_module_params: Vec<(String, Option<String>)>,
+    disabled_params: Vec<String>,
+    expands_init_env: bool,
+    dumps_init: bool,
+    log_level: u8,
+    numeric_base: NumericBase,
+    trace_hook: Option<fn(&CmdlineEvent<'_>)>,
+}
+
+impl KernelCmdline {
+    /// The maximum number of the tokens parsed from the command lines.
+    ///
+    /// The tokens beyond the limit are not parsed, so that a maliciously long
+    /// command line cannot exhaust the memory during early boot.
+    pub const MAX_TOKENS: usize = 4096;
+
+    /// The maximum length of a token in bytes.
+    ///
+    /// A longer token is skipped.
+    pub const MAX_TOKEN_LEN: usize = 4096;
+
+    /// Parses the kernel command line and dispatches its parameters, failing on
+    /// bad tokens.
+    ///
+    /// Unlike the parsing done when this component is initialized, which skips
+    /// the bad tokens, this method rejects the whole command line if any token
+    /// is bad. All the tokens are validated before any parameter is dispatched
+    /// to its handler.
+    pub fn try_from_cmdline(cmdline: &'static str) -> Result<Self, CmdlineError> {
+        let mut kcmdline_end = false;
+        for arg in split_arg(cmdline) {
+            if arg.contains('\0') {
+                return Err(CmdlineError::NulInValue);
+            }
+            if contains_control_char(arg) {
+                return Err(CmdlineError::ControlChar(arg));
+            }
+            // The tokens after "--" are forwarded to init as is.
+            if kcmdline_end {
+                continue;
+            }
+            if arg == "--" {
+                kcmdline_end = true;
+                continue;
+            }
+            if split_key_value(arg, |_| None).0.is_empty() {
+                return Err(CmdlineError::MalformedToken(arg));
+            }
+        }
+
+        Ok(dispatch_params_from_parts(Tokens::Line(cmdline), ""))
+    }
+
+    /// Returns the kernel command line parsed during the initialization of
+    /// this component, or `None` if it has not been parsed yet.
+    ///
+    /// This is for the code that may run before this component is initialized,
+    /// e.g., in another component that does not depend on this one.
+    pub fn try_get() -> Option<&'static KernelCmdline> {
+        try_get_parsed(&KERNEL_CMDLINE)
+    }
+
+    /// Returns the kernel command line parsed during the initialization of
+    /// this component.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the command line has not been parsed yet. It is
+    /// safe to call from the components that depend on this one, which are
+    /// initialized after it. Otherwise, use [`Self::try_get`].
+    pub fn get() -> &'static KernelCmdline {
+        get_parsed(&KERNEL_CMDLINE)
+    }
+
+    /// Parses a command line written one or more tokens per line and
+    /// dispatches its parameters.
+    ///
+    /// The lines are joined with spaces and parsed as a single command line,
+    /// skipping the blank lines and the comment lines starting with `#`. This
+    /// suits a file of parameters such as `cmdline.append`.
+    pub fn from_multiline(text: &str) -> Self {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        dispatch_params_from_parts(Tokens::Line(&lines.join(" ")), "")
+    }
+
+    /// Returns the parsed command line.
+    ///
+    /// The command line is the literal input before tokenization, including
+    /// its whitespace and quotes. If the input is given as split tokens, they
+    /// are joined with spaces. The fragments parsed with [`Self::extend_from`]
+    /// are appended, separated by spaces. The values of the sensitive
+    /// parameters are kept as is, so use [`Self::to_cmdline_string_redacted`]
+    /// for logging.
+    pub fn cmdline(&self) -> &str {
+        &self.cmdline
+    }
+
+    /// Returns the content of `/proc/cmdline`, i.e., [`Self::cmdline`] followed
+    /// by a newline.
+    ///
+    /// Like Linux, the command line is kept verbatim, so the `--` and the init
+    /// arguments after it are included, although they are not kernel
+    /// parameters. The values of the sensitive parameters are included as well.
+    pub fn proc_cmdline_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.cmdline.len() + 1);
+        bytes.extend_from_slice(self.cmdline.as_bytes());
+        bytes.push(b'\n');
+        bytes
+    }
+
+    /// Returns the parsed command line with the values of the sensitive
+    /// parameters replaced by `****`.
+    ///
+    /// The tokens are joined by single spaces.
+    pub fn to_cmdline_string_redacted(&self) -> String {
+        let registry = Registry::new();
+        let tokens: Vec<_> = split_arg(&self.cmdline)
+            .map(|arg| redact_token(arg, &registry))
+            .collect();
+        tokens.join(" ")
+    }
+
+    /// Returns the path of the init executable given by the `init` parameter.
+    ///
+    /// If `init` appears multiple times, the last value wins. Like the other
+    /// parameters, `init` can be disabled with `cmdline_disable`. Without `init`,
+    /// the kernel falls back to the default paths such as `/sbin/init`. The
+    /// path is not part of [`Self::init_proc_args`]: the kernel passes it as
+    /// `argv[0]`, before the arguments after `--`.
+    pub fn initproc_path(&self) -> Option<&str> {
+        self.initproc_path.as_deref()
+    }
+
+    /// Returns the arguments to be passed to the init process.
+    pub fn init_proc_args(&self) -> &InitprocArgs {
+        &self.init_proc_args
+    }
+
+    /// Returns the messages describing the problems found during parsing.
+    ///
+    /// The problems include skipped or malformed tokens, invalid values, and
+    /// unimplemented parameters. The same messages are logged as warnings
+    /// during parsing, but the logger may not be ready that early in the boot.
+    /// The messages are kept here so that they can be retrieved later.
+    ///
+    /// A message is only allocated when there is a problem, so a well-formed
+    /// command line costs nothing here.
+    ///
+    /// The messages depend on the `cmdline_loglevel` parameter. At level 0, no
+    /// message is recorded. At level 3, the messages also describe where each
+    /// token is routed, e.g., to its handler or to the init process.
+    pub fn diagnostics(&self) -> &[String] {
+        &self.diagnostics
+    }
+
+    /// Returns the statistics of the tokens processed during parsing.
+    pub fn stats(&self) -> CmdlineStats {
+        self.stats
+    }
+
+    /// Returns the number of the distinct registered parameters on the command line.
+    ///
+    /// A parameter that appears multiple times is counted once. So are all the
+    /// parameters under the prefix of a [`crate::define_prefix_param!`] or a
+    /// [`crate::define_kernel_param_module!`].
+    pub fn param_count(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns the registered parameters on the command line that are set up
+    /// in the early phase.
+    pub fn early_params(&self) -> impl Iterator<Item = &'static KernelParam> + '_ {
+        self.params.iter().copied().filter(|param| param.is_early())
+    }
+
+    /// Returns the registered parameters on the command line that are set up
+    /// in the late phase.
+    pub fn late_params(&self) -> impl Iterator<Item = &'static KernelParam> + '_ {
+        self.params
+            .iter()
+            .copied()
+            .filter(|param| !param.is_early())
+    }
+
+    /// Returns whether any parameter on the command line is registered with
+    /// [`crate::define_unimplemented_param!`].
+    pub fn has_unimplemented(&self) -> bool {
+        self.unimplemented_params().next().is_some()
+    }
+
+    /// Returns the names of the parameters on the command line that are
+    /// registered with [`crate::define_unimplemented_param!`].
+    pub fn unimplemented_params(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.params
+            .iter()
+            .filter(|param| !param.implemented)
+            .map(|param| param.name)
+    }
+
+    /// Returns the names and values of the parameters on the command line that
+    /// have taken effect.
+    ///
+    /// Unlike [`Self::early_params`] and [`Self::late_params`], a parameter is
+    /// only included if it is implemented, is not disabled, and is set up
+    /// without errors. The parameters are yielded in the order they are set
+    /// up. A repeatable parameter is yielded once per occurrence, while others
+    /// are yielded once with their last values. The value of a sensitive
+    /// parameter is replaced with `****`.
+    ///
+    /// A successful setup does not always mean that the value is stored. If a
+    /// parameter stored in a [`spin::Once`] is set up again, e.g., by
+    /// [`Self::extend_from`], the new value is still yielded even though the
+    /// storage keeps the first value.
+    pub fn effective_params(&self) -> impl Iterator<Item = (&str, Option<&str>)> + '_ {
+        self.effective_params
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+
+    /// Returns the names and values of the unknown module parameters that have
+    /// been dropped.
+    ///
+    /// A module parameter is an unrecognized parameter whose name contains a
+    /// dot, e.g. `mydriver.opt=1`. It is neither dispatched nor forwarded to
+    /// the init process. The parameters are yielded in the order of the command
+    /// line, each with its own diagnostic.
+    pub fn dropped_module_params(&self) -> impl Iterator<Item = (&str, Option<&str>)> + '_ {
+        self.dropped_module_params
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+
+    /// Returns a human-readable report of the parsed command line.
+    ///
+    /// The report has a line for each registered parameter on the command
+    /// line, with its flags and the values that have taken effect (see
+    /// [`Self::effective_params`]), followed by the lines of the init `argv`
+    /// and `envp`. For example:
+    ///
+    /// ```text
+    /// console (late): console=ttyS0 console=tty0
+    /// nosmp (early, unimplemented): not in effect
+    /// argv[0]: single
+    /// envp[0]: TERM=linux
+    /// ```
+    ///
+    /// Unlike the output of [`fmt::Debug`], the format is meant to be stable.
+    pub fn debug_dump(&self) -> String {
+        let mut dump = String::new();
+        for param in &self.params {
+            let mut values = self
+                .effective_params()
+                .filter(|(name, _)| *name == param.name || param.matches_prefix(name).is_some())
+                .peekable();
+            dump.push_str(&format!("{}:", param));
+            if values.peek().is_none() {
+                dump.push_str(" not in effect");
+            }
+            for (name, value) in values {
+                dump.push(' ');
+                dump.push_str(name);
+                if let Some(value) = value {
+                    dump.push('=');
+                    dump.push_str(value);
+                }
+            }
+            dump.push('\n');
+        }
+        dump.push_str(&self.init_proc_args.to_string());
+        dump
+    }
+
+    /// Returns the names of the required parameters that are not on the command line.
+    ///
+    /// A parameter is required if it is defined with `required = true`. The
+    /// component initialization panics if any of them is missing. A required
+    /// parameter disabled by `cmdline_disable` is still considered present.
+    pub fn missing_required_params(&self) -> Vec<&'static str> {
+        self.missing_params_in(registered_params())
+    }
+
+    fn missing_params_in(
+        &self,
+        params: impl IntoIterator<Item = &'static KernelParam>,
+    ) -> Vec<&'static str> {
+        params
+            .into_iter()
+            .filter(|param| param.required)
+            .filter(|param| !self.params.iter().any(|p| p.name == param.name))
+            .map(|param| param.name)
+            .collect()
+    }
+
+    /// Parses an additional command-line fragment and merges it into this one.
+    ///
+    /// The parameters in the fragment are dispatched to their handlers. Note
+    /// that a parameter with a `spin::Once<T>` storage keeps its value if it
+    /// has been set. The init arguments and environment variables in the
+    /// fragment are appended after the existing ones in order.
+    ///
+    /// The fragment is parsed on its own: The tokens after its first `--` are
+    /// init arguments, and the tokens before are kernel parameters, even if a
+    /// `--` has been seen in this command line. A second `--` in the fragment
+    /// is forwarded to the init process as an argument. The inline flags such
+    /// as `cmdline_strict` only apply within the fragment, except that
+    /// `init_expand_env` and `dump_init` stay enabled once enabled.
+    pub fn extend_from(&mut self, cmdline: &str) {
+        let registry = Registry::new();
+        let argv_start = self.init_proc_args.argv.len();
+        let envp_start = self.init_proc_args.envp.len();
+        self.numeric_base = find_numeric_base(Tokens::Line(cmdline), &registry).unwrap_or_default();
+        let occurrences = group_params(Tokens::Line(cmdline), &registry, self);
+        if !self.cmdline.is_empty() {
+            self.cmdline.push(' ');
+        }
+        self.cmdline.push_str(cmdline);
+        self.finish_init_args(argv_start, envp_start);
+        self.set_up_params(&registry, occurrences);
+    }
+
+    // Post-processes the init arguments from `argv[argv_start]` and
+    // `envp[envp_start]`, which have been added by the last parsed command line.
+    fn finish_init_args(&mut self, argv_start: usize, envp_start: usize) {
+        if self.expands_init_env {
+            let mut undefined = Vec::new();
+            self.init_proc_args
+                .expand_env_refs(argv_start, envp_start, &mut undefined);
+            for var_name in undefined {
+                self.report(format!(
+                    "init argument references undefined variable '{}'",
+                    var_name
+                ));
+            }
+        }
+        if self.dumps_init {
+            for line in self.init_proc_args.to_string().lines() {
+                ostd::info!("{}", line);
+            }
+        }
+    }
+
+    // Records the recognized params and dispatches them to their handlers.
+    //
+    // The groups are already sorted by name. The stable sort by priority keeps
+    // the setup order deterministic.
+    fn set_up_params(&mut self, registry: &Registry, occurrences: ParamOccurrences) {
+        let ParamOccurrences {
+            grouped,
+            indices,
+            prefixed,
+        } = occurrences;
+
+        let recognized_params = grouped
+            .keys()
+            .filter_map(|name| registry.get(name.as_str()))
+            .chain(prefixed.iter().map(|(param, _, _)| *param));
+        for param in recognized_params {
+            if !self.params.iter().any(|p| p.name == param.name) {
+                self.params.push(param);
+            }
+        }
+
+        // Mutually exclusive params are only reported. They are still set up.
+        let mut group_members: BTreeMap<&'static str, &'static str> = BTreeMap::new();
+        for param in grouped
+            .keys()
+            .filter_map(|name| registry.get(name.as_str()))
+        {
+            let Some(group) = param.group else {
+                continue;
+            };
+            if let Some(prev_name) = group_members.insert(group, param.name) {
+                self.report(format!(
+                    "kernel parameters '{}' and '{}' conflict in group '{}'",
+                    prev_name, param.name, group
+                ));
+            }
+        }
+
+        let (mut early_params, mut params): (Vec<_>, Vec<_>) = grouped
+            .iter()
+            .filter_map(|(name, occurrences)| registry.get(name.as_str()).map(|p| (p, occurrences)))
+            .partition(|(p, _)| p.early);
+        early_params.sort_by_key(|(p, _)| Reverse(p.priority));
+        params.sort_by_key(|(p, _)| Reverse(p.priority));
+
+        for (param, occurrences) in early_params.into_iter().chain(params) {
+            if self.is_disabled(param) {
+                continue;
+            }
+            if !param.implemented {
+                self.report(format!(
+                    "kernel parameter '{}' is not yet implemented",
+                    param.name
+                ));
+                continue;
+            }
+            let mut notes = ParamNotes::new(self.numeric_base);
+            let setup_result = match param.setup_fn {
+                SetupFn::Grouped(setup_fn) => setup_fn(occurrences, &mut notes),
+                SetupFn::Indexed(setup_fn) => {
+                    let indexed: Vec<_> = indices
+                        .get(param.name)
+                        .into_iter()
+                        .flatten()
+                        .copied()
+                        .zip(occurrences.iter().copied())
+                        .collect();
+                    setup_fn(&indexed, &mut notes)
+                }
+                SetupFn::Builtin(setup_fn) => setup_fn(self, occurrences),
+                SetupFn::Prefixed(_) => continue,
+            };
+            for note in notes.into_messages() {
+                self.report(note);
+            }
+            if let Err(err) = setup_result {
+                // All the values of a repeatable param are parsed at once, so
+                // the invalid one is unknown.
+                let value = if param.repeatable {
+                    None
+                } else {
+                    occurrences.last().copied().flatten()
+                };
+                self.report_setup_error(param, param.name, value, err);
+            } else if param.repeatable {
+                for value in occurrences {
+                    self.record_effective(param, param.name, *value);
+                }
+            } else if let Some(value) = occurrences.last() {
+                self.record_effective(param, param.name, *value);
+            }
+        }
+
+        // The prefixed params are dispatched occurrence by occurrence after the others.
+        for (param, suffix, value) in prefixed {
+            if self.is_disabled(param) {
+                continue;
+            }
+            let SetupFn::Prefixed(setup_fn) = param.setup_fn else {
+                continue;
+            };
+            let name = param.name_with_suffix(&suffix);
+            match setup_fn(&suffix, value) {
+                Ok(()) => self.record_effective(param, &name, value),
+                Err(err) => self.report_setup_error(param, &name, value, err),
+            }
+        }
+
+        self.run_validators(inventory::iter::<CmdlineValidator>);
+    }
+
+    fn run_validators<'a>(&mut self, validators: impl IntoIterator<Item = &'a CmdlineValidator>) {
+        for validator in validators {
+            if let Err(reason) = (validator.validate_fn)() {
+                self.report(format!(
+                    "kernel parameter validator '{}' failed: {}",
+                    validator.name, reason
+                ));
+            }
+        }
+    }
+
+    fn is_disabled(&self, param: &KernelParam) -> bool {
+        self.disabled_params.iter().any(|name| name == param.name)
+    }
+
+    fn report(&mut self, message: String) {
+        if self.log_level == 0 {
+            return;
+        }
+        if self.log_level >= 2 {
+            ostd::warn!("{}", message);
+        }
+        self.diagnostics.push(message);
+    }
+
+    // Counts a dropped token.
+    fn ignore(&mut self, token: &str) {
+        self.stats.ignored += 1;
+        self.emit(CmdlineEvent::Dropped(token));
+    }
+
+    // Counts a token taken by the dispatcher itself, e.g., `cmdline_strict`.
+    fn take_builtin(&mut self, token: &str) {
+        self.stats.matched += 1;
+        self.emit(CmdlineEvent::Builtin(token));
+    }
+
+    fn emit(&self, event: CmdlineEvent<'_>) {
+        if let Some(hook) = self.trace_hook {
+            hook(&event);
+        }
+    }
+
+    // Records a routing decision of the parser, which is only of interest at
+    // the highest log level.
+    fn trace(&mut self, message: impl FnOnce() -> String) {
+        if self.log_level < MAX_LOG_LEVEL {
+            return;
+        }
+        let message = message();
+        ostd::info!("{}", message);
+        self.diagnostics.push(message);
+    }
+
+    // Records that the parameter `name` of `param` has been set up with `value`.
+    fn record_effective(&mut self, param: &KernelParam, name: &str, value: Option<&str>) {
+        let value = value.map(|value| if param.sensitive { "****" } else { value });
+        self.effective_params
+            .push((name.to_string(), value.map(ToString::to_string)));
+    }
+
+    // Reports the failed setup of the parameter `name` of `param`. The value is
+    // named in the report if it is known to be the invalid one.
+    fn report_setup_error(
+        &mut self,
+        param: &KernelParam,
+        name: &str,
+        value: Option<&str>,
+        err: ParamError,
+    ) {
+        match err {
+            ParamError::InvalidValue => match value {
+                Some(value) => self.report(format!(
+                    "invalid value '{}' for kernel parameter '{}'",
+                    if param.sensitive { "****" } else { value },
+                    name
+                )),
+                None => self.report(format!("invalid value for kernel parameter '{}'", name)),
+            },
+            ParamError::MissingValue => {
+                self.report(format!("kernel parameter '{}' requires a value", name))
+            }
+        }
+    }
+
+    fn forward_arg(&mut self, arg: &str) {
+        if let Some(argv_entry) = self.init_entry_of(arg) {
+            self.init_proc_args.argv.push(argv_entry);
+            self.stats.forwarded += 1;
+            self.emit(CmdlineEvent::InitArg(arg));
+            self.trace(|| format!("forwarding '{}' to init as an argument", arg));
+        }
+    }
+
+    fn forward_env(&mut self, env: &str) {
+        if let Some(envp_entry) = self.init_entry_of(env) {
+            self.init_proc_args.envp.push(envp_entry);
+            self.stats.forwarded += 1;
+            self.emit(CmdlineEvent::InitEnv(env));
+            self.trace(|| format!("forwarding '{}' to init as an environment variable", env));
+        }
+    }
+
+    // Pattern 'entry=value' is treated as the init environment if `to_env`,
+    // and as the init argument otherwise. Pattern 'entry' without value is
+    // treated as the init argument.
+    fn forward_param(&mut self, key: &str, value: Option<&str>, to_env: bool) {
+        match value {
+            Some(value) if to_env => self.forward_env(&(key.to_string() + "=" + value)),
+            Some(value) => self.forward_arg(&(key.to_string() + "=" + value)),
+            None => self.forward_arg(key),
+        }
+    }
+
+    // Converts a token into an entry of `argv` or `envp` of the init process.
+    //
+    // A token containing a NUL byte cannot be passed to the init process, so it
+    // is dropped instead of failing the parsing.
+    fn init_entry_of(&mut self, token: &str) -> Option<CString> {
+        let Ok(entry) = CString::new(token) else {
+            self.report(format!(
+                "ignoring init argument '{}' containing a NUL byte",
+                token.escape_default()
+            ));
+            self.ignore(token);
+            return None;
+        };
+        Some(entry)
+    }
+}
+
+/// A decision of the parser on a token, observed with [`set_cmdline_trace`].
+#[derive(Clone, Copy, Debug)]
+pub enum CmdlineEvent<'a> {
+    /// The token is taken as a registered parameter.
+    ///
+    /// Whether the parameter is set up early or late is given by
+    /// [`KernelParam::is_early`].
+    Param {
+        token: &'a str,
+        param: &'static KernelParam,
+    },
+    /// The token is taken by the parser itself, e.g., `cmdline_strict`.
+    Builtin(&'a str),
+    /// The token is forwarded to the init process as an argument.
+    InitArg(&'a str),
+    /// The token is forwarded to the init process as an environment variable.
+    InitEnv(&'a str),
+    /// The token is dropped, e.g., because it is malformed.
+    Dropped(&'a str),
+}
+
+static TRACE_HOOK: Mutex<Option<fn(&CmdlineEvent<'_>)>> = Mutex::new(None);
+
+/// Sets the hook that observes each decision of the parser on a token.
+///
+/// The hook is called in the order of the tokens, from the command lines
+/// parsed after it is set. The parser reads the hook once per command line,
+/// so an unset hook costs nothing per token. `None` unsets the hook.
+pub fn set_cmdline_trace(hook: Option<fn(&CmdlineEvent<'_>)>) {
+    *TRACE_HOOK.lock() = hook;
+}
+
+/// Errors while parsing the kernel command line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CmdlineError {
+    /// The token is malformed, e.g., it has no name before `=`.
+    MalformedToken(&'static str),
+    /// The token contains a NUL byte, which cannot be passed to the init process.
+    NulInValue,
+    /// The token contains a control character other than whitespace.
+    ControlChar(&'static str),
+}
+
+/// Statistics of the tokens processed while parsing the kernel command line.
+///
+/// The `--` separator is not counted as a token. Every other token is counted
+/// in exactly one of `matched`, `forwarded`, and `ignored`. A parameter that
+/// is passed through to the init process is counted as `forwarded`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CmdlineStats {
+    /// The number of the processed tokens.
+    pub tokens: usize,
+    /// The number of the tokens consumed as kernel parameters.
+    pub matched: usize,
+    /// The number of the tokens forwarded to the init process.
+    pub forwarded: usize,
+    /// The number of the tokens dropped because they are malformed or not allowed.
+    pub ignored: usize,
+}
 
 /// The arguments passed to the init process, extracted from the kernel command line.
 #[derive(Debug, PartialEq)]
@@ -22,198 +802,2923 @@ pub struct InitprocArgs {
     envp: Vec<CString>,
 }
 
-impl InitprocArgs {
-    /// Returns the argument vector (`argv`) of the init process.
-    pub fn argv(&self) -> &[CString] {
-        &self.argv
+impl InitprocArgs {
+    /// Returns the argument vector (`argv`) of the init process.
+    pub fn argv(&self) -> &[CString] {
+        &self.argv
+    }
+
+    /// Returns the environment vector (`envp`) of the init process.
+    pub fn envp(&self) -> &[CString] {
+        &self.envp
+    }
+
+    /// Returns the entries of [`Self::argv`] as string slices.
+    ///
+    /// The entries come from the UTF-8 command line, and the tokens with NUL
+    /// bytes have been dropped, so every entry is yielded. An entry that is
+    /// not valid UTF-8 would be skipped.
+    pub fn argv_str(&self) -> impl Iterator<Item = &str> {
+        self.argv.iter().filter_map(|arg| arg.to_str().ok())
+    }
+
+    /// Returns the entries of [`Self::envp`] as string slices.
+    ///
+    /// See [`Self::argv_str`].
+    pub fn envp_str(&self) -> impl Iterator<Item = &str> {
+        self.envp.iter().filter_map(|env| env.to_str().ok())
+    }
+
+    // Substitutes the `$VAR` and `${VAR}` references in the arguments from
+    // `argv[argv_start]` and in the values of the entries from
+    // `envp[envp_start]`. An `argv` entry sees the whole environment, while an
+    // `envp` entry only sees the entries before it. The names of the undefined
+    // variables are pushed to `undefined`, and their references are kept.
+    fn expand_env_refs(
+        &mut self,
+        argv_start: usize,
+        envp_start: usize,
+        undefined: &mut Vec<String>,
+    ) {
+        for i in envp_start..self.envp.len() {
+            let env = self.envp[i].to_bytes();
+            let Some(eq_pos) = env.iter().position(|b| *b == b'=') else {
+                continue;
+            };
+            let (key, value) = env.split_at(eq_pos + 1);
+            let mut expanded = key.to_vec();
+            expanded.extend(expand_refs(value, &self.envp[..i], undefined));
+            self.envp[i] = CString::new(expanded).unwrap_or_default();
+        }
+
+        for i in argv_start..self.argv.len() {
+            let expanded = expand_refs(self.argv[i].to_bytes(), &self.envp, undefined);
+            self.argv[i] = CString::new(expanded).unwrap_or_default();
+        }
+    }
+}
+
+// Expands the variable references in `input` with the values in `envp`.
+//
+// A name consists of ASCII alphanumerics and underscores. A `$` that does not
+// start a reference, e.g. `$` followed by a space or an unclosed `${`, is kept.
+fn expand_refs(input: &[u8], envp: &[CString], undefined: &mut Vec<String>) -> Vec<u8> {
+    let is_name_byte = |b: &u8| b.is_ascii_alphanumeric() || *b == b'_';
+
+    let mut output = Vec::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(dollar_pos) = rest.iter().position(|b| *b == b'$') {
+        output.extend_from_slice(&rest[..dollar_pos]);
+        let after_dollar = &rest[dollar_pos + 1..];
+
+        let (var_name, ref_len) = if let Some(braced) = after_dollar.strip_prefix(b"{") {
+            match braced.iter().position(|b| *b == b'}') {
+                Some(name_len) if braced[..name_len].iter().all(is_name_byte) => {
+                    (&braced[..name_len], name_len + 3)
+                }
+                _ => (&[][..], 0),
+            }
+        } else {
+            let name_len = after_dollar
+                .iter()
+                .position(|b| !is_name_byte(b))
+                .unwrap_or(after_dollar.len());
+            (&after_dollar[..name_len], name_len + 1)
+        };
+        if var_name.is_empty() {
+            output.push(b'$');
+            rest = after_dollar;
+            continue;
+        }
+
+        // The last definition of a variable wins.
+        let value = envp
+            .iter()
+            .rev()
+            .find_map(|env| env.to_bytes().strip_prefix(var_name)?.strip_prefix(b"="));
+        match value {
+            Some(value) => output.extend_from_slice(value),
+            None => {
+                output.extend_from_slice(&rest[dollar_pos..dollar_pos + ref_len]);
+                undefined.push(String::from_utf8_lossy(var_name).into_owned());
+            }
+        }
+        rest = &rest[dollar_pos + ref_len..];
+    }
+    output.extend_from_slice(rest);
+
+    output
+}
+
+/// Lists the arguments one per line, e.g. `argv[0]: sh` and `envp[0]: HOME=/`.
+impl fmt::Display for InitprocArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, arg) in self.argv.iter().enumerate() {
+            writeln!(f, "argv[{}]: {}", i, arg.to_string_lossy())?;
+        }
+        for (i, env) in self.envp.iter().enumerate() {
+            writeln!(f, "envp[{}]: {}", i, env.to_string_lossy())?;
+        }
+        Ok(())
+    }
+}
+
+/// A kernel command-line parameter registered with the parameter-defining macros.
+#[derive(Debug)]
+pub struct KernelParam {
+    name: &'static str,
+    setup_fn: SetupFn,
+    early: bool,
+    implemented: bool,
+    passthrough: bool,
+    priority: u8,
+    separator: char,
+    group: Option<&'static str>,
+    allows_empty: bool,
+    sensitive: bool,
+    required: bool,
+    repeatable: bool,
+    module: bool,
+    help: &'static str,
+    location: &'static str,
+    storage_addr: Option<fn() -> usize>,
+}
+
+impl KernelParam {
+    /// The priority of a parameter if it does not declare one.
+    pub const DEFAULT_PRIORITY: u8 = 128;
+
+    #[doc(hidden)]
+    pub const fn new(name: &'static str, setup_fn: GroupedSetupFn, early: bool) -> KernelParam {
+        if Self::contains_byte(name, b'-') {
+            panic!("kernel param registration must not contain '-' (use '_')");
+        }
+        // The name of a token ends before the first `=`, so such a name never matches.
+        if Self::contains_byte(name, b'=') {
+            panic!("kernel param registration must not contain '=' (the value is separate)");
+        }
+        KernelParam {
+            name,
+            setup_fn: SetupFn::Grouped(setup_fn),
+            early,
+            implemented: true,
+            passthrough: false,
+            priority: Self::DEFAULT_PRIORITY,
+            separator: '=',
+            group: None,
+            allows_empty: true,
+            sensitive: false,
+            required: false,
+            repeatable: false,
+            module: false,
+            help: "",
+            location: "",
+            storage_addr: None,
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn with_priority(self, priority: u8) -> KernelParam {
+        KernelParam { priority, ..self }
+    }
+
+    #[doc(hidden)]
+    pub const fn in_group(self, group: &'static str) -> KernelParam {
+        KernelParam {
+            group: Some(group),
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn with_allow_empty(self, allows_empty: bool) -> KernelParam {
+        KernelParam {
+            allows_empty,
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn with_sensitive(self, sensitive: bool) -> KernelParam {
+        KernelParam { sensitive, ..self }
+    }
+
+    #[doc(hidden)]
+    pub const fn with_required(self, required: bool) -> KernelParam {
+        KernelParam { required, ..self }
+    }
+
+    #[doc(hidden)]
+    pub const fn with_help(self, help: &'static str) -> KernelParam {
+        KernelParam { help, ..self }
+    }
+
+    #[doc(hidden)]
+    pub const fn at_location(self, location: &'static str) -> KernelParam {
+        KernelParam { location, ..self }
+    }
+
+    #[doc(hidden)]
+    pub const fn with_storage_addr(self, storage_addr: fn() -> usize) -> KernelParam {
+        KernelParam {
+            storage_addr: Some(storage_addr),
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn repeatable(self) -> KernelParam {
+        KernelParam {
+            repeatable: true,
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn colon_separated(self) -> KernelParam {
+        KernelParam {
+            separator: ':',
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn new_unimplemented(name: &'static str) -> KernelParam {
+        KernelParam {
+            implemented: false,
+            ..Self::new(name, Self::setup_nothing, false)
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn with_passthrough(self, passthrough: bool) -> KernelParam {
+        KernelParam {
+            passthrough,
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn new_indexed(name: &'static str, setup_fn: IndexedSetupFn) -> KernelParam {
+        KernelParam {
+            setup_fn: SetupFn::Indexed(setup_fn),
+            ..Self::new(name, Self::setup_nothing, false)
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn new_prefixed(prefix: &'static str, setup_fn: PrefixedSetupFn) -> KernelParam {
+        KernelParam {
+            setup_fn: SetupFn::Prefixed(setup_fn),
+            ..Self::new(prefix, Self::setup_nothing, false)
+        }
+    }
+
+    const fn new_builtin(name: &'static str, setup_fn: BuiltinSetupFn) -> KernelParam {
+        KernelParam {
+            setup_fn: SetupFn::Builtin(setup_fn),
+            ..Self::new(name, Self::setup_nothing, false)
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn new_module(module: &'static str, setup_fn: PrefixedSetupFn) -> KernelParam {
+        if Self::contains_byte(module, b'.') {
+            panic!("kernel param module must not contain '.' (the sub-keys follow it)");
+        }
+        KernelParam {
+            module: true,
+            ..Self::new_prefixed(module, setup_fn)
+        }
+    }
+
+    fn setup_nothing(
+        _occurrences: &[Option<&str>],
+        _notes: &mut ParamNotes,
+    ) -> Result<(), ParamError> {
+        Ok(())
+    }
+
+    /// Returns the name of the parameter.
+    ///
+    /// For a parameter defined with [`crate::define_prefix_param!`], this is the
+    /// prefix. For one defined with [`crate::define_kernel_param_module!`], this
+    /// is the module name without the trailing `.`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns whether `name` is the name of the parameter.
+    ///
+    /// Hyphens and underscores in `name` are interchangeable, e.g., both
+    /// `log-level` and `log_level` match the parameter named `log_level`.
+    pub fn matches_name(&self, name: &str) -> bool {
+        name.len() == self.name.len()
+            && name
+                .bytes()
+                .zip(self.name.bytes())
+                .all(|(byte, expected)| byte == expected || (byte == b'-' && expected == b'_'))
+    }
+
+    /// Returns whether the parameter is set up in the early phase.
+    ///
+    /// All the early parameters are set up before the late ones.
+    pub fn is_early(&self) -> bool {
+        self.early
+    }
+
+    /// Returns the group of mutually exclusive parameters that the parameter belongs to.
+    pub fn group(&self) -> Option<&'static str> {
+        self.group
+    }
+
+    /// Returns the short description of the parameter.
+    ///
+    /// The description is empty if the parameter is defined without one.
+    pub fn help(&self) -> &'static str {
+        self.help
+    }
+
+    /// Returns whether the value of the parameter is masked in logs.
+    pub fn is_sensitive(&self) -> bool {
+        self.sensitive
+    }
+
+    /// Returns whether the boot fails if the parameter is not on the command line.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Returns the priority of the parameter.
+    ///
+    /// Within the same phase (early or late), parameters with higher priorities
+    /// are set up first. Parameters with the same priority are set up in the
+    /// order of their names.
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    /// Returns the rest of `entry` after the prefix if the parameter is
+    /// defined with [`crate::define_prefix_param!`] or
+    /// [`crate::define_kernel_param_module!`] and matches `entry`.
+    ///
+    /// For example, the module `"e1000"` matches `"e1000.debug"` and returns
+    /// `"debug"`, but matches neither `"e1000"` nor `"e1000x.debug"`. A prefix
+    /// parameter matches the entries starting with its prefix as is. `entry` is
+    /// a parameter name with its hyphens replaced by underscores. A parameter
+    /// defined otherwise matches no entry.
+    pub fn matches_prefix<'a>(&self, entry: &'a str) -> Option<&'a str> {
+        let SetupFn::Prefixed(_) = self.setup_fn else {
+            return None;
+        };
+        let rest = entry.strip_prefix(self.name)?;
+        if self.module {
+            rest.strip_prefix('.')
+        } else {
+            Some(rest)
+        }
+    }
+
+    // Returns the name of the entry that matches the parameter with `suffix`,
+    // i.e., the reverse of `matches_prefix`.
+    fn name_with_suffix(&self, suffix: &str) -> String {
+        if self.module {
+            format!("{}.{}", self.name, suffix)
+        } else {
+            self.name.to_string() + suffix
+        }
+    }
+
+    const fn contains_byte(s: &'static str, byte: u8) -> bool {
+        let bytes = s.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == byte {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+}
+
+/// Describes the parameter in a line, e.g. `tsc (late, unimplemented)`.
+///
+/// The name of a parameter defined with [`crate::define_prefix_param!`] ends
+/// with `*`, and so does the name of a module followed by `.`, e.g. `e1000.*`.
+impl fmt::Display for KernelParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.module {
+            write!(f, ".")?;
+        }
+        if matches!(self.setup_fn, SetupFn::Prefixed(_)) {
+            write!(f, "*")?;
+        }
+        write!(f, " ({}", if self.early { "early" } else { "late" })?;
+        if !self.implemented {
+            write!(f, ", unimplemented")?;
+        }
+        if self.passthrough {
+            write!(f, ", passthrough")?;
+        }
+        write!(f, ")")
+    }
+}
+
+inventory::collect!(KernelParam);
+
+/// A check of the parameters as a whole, registered with
+/// [`crate::register_cmdline_validator!`].
+///
+/// The validators run after all the parameters of a command line are set up,
+/// so they can check the constraints among parameters, e.g. a minimum is not
+/// greater than a maximum. A failure is reported as a diagnostic.
+#[derive(Debug)]
+pub struct CmdlineValidator {
+    name: &'static str,
+    validate_fn: fn() -> Result<(), &'static str>,
+}
+
+impl CmdlineValidator {
+    #[doc(hidden)]
+    pub const fn new(name: &'static str, validate_fn: fn() -> Result<(), &'static str>) -> Self {
+        Self { name, validate_fn }
+    }
+
+    /// Returns the name of the validator.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+inventory::collect!(CmdlineValidator);
+
+// The setup function of a parameter that receives all its occurrences at once.
+type GroupedSetupFn =
+    fn(occurrences: &[Option<&str>], notes: &mut ParamNotes) -> Result<(), ParamError>;
+
+// The setup function of a parameter that receives the indices of its occurrences.
+type IndexedSetupFn =
+    fn(occurrences: &[(usize, Option<&str>)], notes: &mut ParamNotes) -> Result<(), ParamError>;
+
+// The setup function of a parameter whose value belongs to the command line
+// being parsed, e.g., `init`.
+type BuiltinSetupFn =
+    fn(cmdline: &mut KernelCmdline, occurrences: &[Option<&str>]) -> Result<(), ParamError>;
+
+// The setup function of a parameter that receives each occurrence under its prefix.
+type PrefixedSetupFn = fn(suffix: &str, value: Option<&str>) -> Result<(), ParamError>;
+
+#[derive(Debug)]
+enum SetupFn {
+    /// Receives all the occurrences of the parameter at once.
+    Grouped(GroupedSetupFn),
+    /// Receives all the occurrences of the parameter at once, each with the
+    /// index of its token among all the processed tokens.
+    Indexed(IndexedSetupFn),
+    /// Receives all the occurrences of the parameter at once, and stores the
+    /// value in the command line being parsed.
+    Builtin(BuiltinSetupFn),
+    /// Receives each occurrence of a parameter whose name starts with the prefix.
+    Prefixed(PrefixedSetupFn),
+}
+
+/// The lookup from names to the registered parameters.
+struct Registry {
+    params: BTreeMap<&'static str, &'static KernelParam>,
+    prefixed_params: Vec<&'static KernelParam>,
+}
+
+impl Registry {
+    fn new() -> Self {
+        Self::from_params(inventory::iter::<KernelParam>.into_iter().collect())
+    }
+
+    // Builds the lookup from the parameters in any order.
+    //
+    // The parameters are sorted first, so that a duplicate name resolves to the
+    // same parameter however the parameters are linked.
+    fn from_params(mut all_params: Vec<&'static KernelParam>) -> Self {
+        sort_params(&mut all_params);
+
+        let mut params = BTreeMap::new();
+        let mut prefixed_params = Vec::new();
+        for p in all_params {
+            if matches!(p.setup_fn, SetupFn::Prefixed(_)) {
+                prefixed_params.push(p);
+            } else if let Some(prev) = params.insert(p.name, p) {
+                ostd::warn!(
+                    "duplicate kernel parameter '{}' registered at {} and {}; keeping the latter",
+                    prev.name,
+                    prev.location,
+                    p.location
+                );
+            }
+        }
+
+        Registry {
+            params,
+            prefixed_params,
+        }
+    }
+
+    // Finds the parameter with exactly the normalized name.
+    fn get(&self, name: &str) -> Option<&'static KernelParam> {
+        self.params.get(name).copied()
+    }
+
+    // Finds the prefixed parameter with the longest prefix of the normalized name.
+    fn find_prefixed<'a>(&self, name: &'a str) -> Option<(&'static KernelParam, &'a str)> {
+        self.prefixed_params
+            .iter()
+            .filter_map(|param| Some((*param, param.matches_prefix(name)?)))
+            .max_by_key(|(param, _)| param.name.len())
+    }
+}
+
+/// The result of resolving a kernel command-line token against the registered parameters.
+#[derive(Debug)]
+pub enum ResolveResult {
+    /// The token names a registered and implemented parameter.
+    Known(&'static KernelParam),
+    /// The token names a parameter that is recognized but not implemented.
+    Unimplemented(&'static KernelParam),
+    /// The token does not name any registered parameter.
+    Unknown,
+}
+
+/// Resolves a raw kernel command-line token (e.g., `"log_level=3"`) to its parameter.
+///
+/// The name of the token is normalized in the same way as the dispatcher does,
+/// so `"log-level"` resolves to the parameter registered as `"log_level"`.
+pub fn resolve(token: &str) -> ResolveResult {
+    let registry = Registry::new();
+
+    let (key, _) = split_key_value(token, |name| registry.get(name));
+    let normalized = normalize_name(key);
+    let Some(param) = registry
+        .get(&normalized)
+        .or_else(|| registry.find_prefixed(&normalized).map(|(param, _)| param))
+    else {
+        return ResolveResult::Unknown;
+    };
+
+    if param.implemented {
+        ResolveResult::Known(param)
+    } else {
+        ResolveResult::Unimplemented(param)
+    }
+}
+
+/// Returns all the registered parameters, e.g., to list them in a help message.
+///
+/// Each of them can be formatted as a line describing it with
+/// [`core::fmt::Display`].
+///
+/// The parameters are sorted by their names, and then by the source locations
+/// of their definitions. The order of the registry itself depends on how the
+/// kernel is linked, so it is never exposed. If a name is registered more than
+/// once, the last definition in this order is the one in effect.
+pub fn registered_params() -> impl Iterator<Item = &'static KernelParam> {
+    let mut params: Vec<_> = inventory::iter::<KernelParam>.into_iter().collect();
+    sort_params(&mut params);
+    params.into_iter()
+}
+
+fn sort_params(params: &mut [&'static KernelParam]) {
+    params.sort_by_key(|param| (param.name, param.location));
+}
+
+/// Logs all the registered parameters with their descriptions, one per line.
+///
+/// A line looks like `log_level (late): Sets the verbosity of the kernel log`.
+/// The parameters are listed in the order of their names.
+pub fn dump_params() {
+    for param in registered_params() {
+        if param.help.is_empty() {
+            ostd::info!("{}", param);
+        } else {
+            ostd::info!("{}: {}", param, param.help);
+        }
+    }
+}
+
+/// The kernel command line parsed during the initialization of this component.
+///
+/// See [`KernelCmdline::get`] and [`KernelCmdline::try_get`] for the access.
+///
+/// The command line is never reset, even in tests, since the references
+/// returned by the accessors live as long as the kernel. A test that needs
+/// other command lines parses its own with [`KernelCmdline::try_from_cmdline`]
+/// or from split tokens, which return independent instances.
+pub static KERNEL_CMDLINE: Once<KernelCmdline> = Once::new();
+
+// Returns the command line in `cmdline`, or `None` if it has not been parsed yet.
+fn try_get_parsed(cmdline: &Once<KernelCmdline>) -> Option<&KernelCmdline> {
+    cmdline.get()
+}
+
+// Returns the command line in `cmdline`, panicking if it has not been parsed yet.
+fn get_parsed(cmdline: &Once<KernelCmdline>) -> &KernelCmdline {
+    try_get_parsed(cmdline).expect("the kernel command line is accessed before it is parsed")
+}
+
+#[init_component]
+fn init() -> Result<(), ComponentInitError> {
+    if cfg!(debug_assertions) {
+        for (early_name, late_name) in find_same_storage_addr(registered_params()) {
+            ostd::warn!(
+                "early kernel parameter '{}' has the same storage address as late kernel parameter '{}'",
+                early_name,
+                late_name
+            );
+        }
+    }
+
+    let boot_info = ostd::boot::boot_info();
+    KERNEL_CMDLINE.call_once(|| {
+        dispatch_params_from_parts(
+            Tokens::Line(boot_info.kernel_cmdline.as_str()),
+            boot_info.builtin_kernel_cmdline,
+        )
+    });
+
+    let missing_params = KernelCmdline::get().missing_required_params();
+    if !missing_params.is_empty() {
+        panic!(
+            "missing required kernel parameters: {}",
+            missing_params.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+// Finds the early parameters that are registered with the same storage address
+// as a late one.
+//
+// The setup of such an early parameter sees the storage before the late
+// parameter fills it, which is likely a bug. Only the addresses given to the
+// registration macros are compared, so an early parameter that depends on a
+// late one in other ways, e.g., through an accessor or another static, is not
+// found.
+fn find_same_storage_addr<'a>(
+    params: impl IntoIterator<Item = &'a KernelParam>,
+) -> Vec<(&'static str, &'static str)> {
+    let (early_params, late_params): (Vec<_>, Vec<_>) = params
+        .into_iter()
+        .filter_map(|param| param.storage_addr.map(|addr_fn| (param, addr_fn())))
+        .partition(|(param, _)| param.early);
+
+    let late_names: BTreeMap<usize, &'static str> = late_params
+        .into_iter()
+        .map(|(param, addr)| (addr, param.name))
+        .collect();
+    early_params
+        .into_iter()
+        .filter_map(|(param, addr)| late_names.get(&addr).map(|name| (param.name, *name)))
+        .collect()
+}
+
+// Dispatches the parameters from the bootloader command line and the built-in
+// command line, which is logically appended after the bootloader one.
+//
+// The built-in command line only provides defaults: A parameter given by the
+// built-in command line is ignored if the bootloader command line has it.
+fn dispatch_params_from_parts(cmdline: Tokens<'_>, builtin_cmdline: &str) -> KernelCmdline {
+    let mut result = KernelCmdline {
+        cmdline: cmdline.to_line(),
+        initproc_path: None,
+        init_proc_args: InitprocArgs {
+            argv: Vec::new(),
+            envp: Vec::new(),
+        },
+        diagnostics: Vec::new(),
+        stats: CmdlineStats::default(),
+        params: Vec::new(),
+        effective_params: Vec::new(),
+        dropped_module_params: Vec::new(),
+        disabled_params: Vec::new(),
+        expands_init_env: false,
+        dumps_init: false,
+        log_level: DEFAULT_LOG_LEVEL,
+        numeric_base: NumericBase::Dec,
+        trace_hook: *TRACE_HOOK.lock(),
+    };
+
+    // Step 1: Build lookup from registered param name to handler.
+    let registry = Registry::new();
+
+    // The log level affects the parsing of both command lines.
+    result.log_level = find_log_level(cmdline, &registry)
+        .or_else(|| find_log_level(Tokens::Line(builtin_cmdline), &registry))
+        .unwrap_or(DEFAULT_LOG_LEVEL);
+    result.numeric_base = find_numeric_base(cmdline, &registry)
+        .or_else(|| find_numeric_base(Tokens::Line(builtin_cmdline), &registry))
+        .unwrap_or_default();
+
+    // Step 2: Tokenize the command lines and group recognized param by normalized name.
+    let ParamOccurrences {
+        mut grouped,
+        mut indices,
+        mut prefixed,
+    } = group_params(cmdline, &registry, &mut result);
+    let mut builtin_occurrences =
+        group_params(Tokens::Line(builtin_cmdline), &registry, &mut result);
+    for (name, occurrences) in builtin_occurrences.grouped {
+        if let Entry::Vacant(entry) = grouped.entry(name) {
+            let name_indices = builtin_occurrences.indices.remove(entry.key());
+            indices.insert(entry.key().clone(), name_indices.unwrap_or_default());
+            entry.insert(occurrences);
+        }
+    }
+    for (param, suffix, value) in builtin_occurrences.prefixed {
+        if !prefixed.iter().any(|(prev_param, prev_suffix, _)| {
+            prev_param.name == param.name && *prev_suffix == suffix
+        }) {
+            prefixed.push((param, suffix, value));
+        }
+    }
+    result.finish_init_args(0, 0);
+
+    // Step 3: Dispatch each group to its handler.
+    result.set_up_params(
+        &registry,
+        ParamOccurrences {
+            grouped,
+            indices,
+            prefixed,
+        },
+    );
+
+    result
+}
+
+/// Parses the command line already split into tokens, e.g., by the bootloader.
+///
+/// The tokens are parsed as if they were split from a single command line
+/// with [`split_arg`], so a token may contain spaces without being quoted. The
+/// command line of the result, [`KernelCmdline::cmdline`], is the tokens
+/// joined with spaces.
+impl From<&'static [&'static str]> for KernelCmdline {
+    fn from(tokens: &'static [&'static str]) -> Self {
+        dispatch_params_from_parts(Tokens::Split(tokens), "")
+    }
+}
+
+// The tokens of a command line, either to be split or already split.
+#[derive(Clone, Copy)]
+enum Tokens<'a> {
+    Line(&'a str),
+    Split(&'a [&'a str]),
+}
+
+impl<'a> Tokens<'a> {
+    // Iterates over the tokens with their byte ranges.
+    //
+    // The ranges of the split tokens are those in the tokens joined with spaces.
+    // The empty split tokens are skipped, like the empty ones between spaces.
+    fn spans(self) -> impl Iterator<Item = (Range<usize>, &'a str)> {
+        let (line, split) = match self {
+            Tokens::Line(line) => (line, &[][..]),
+            Tokens::Split(split) => ("", split),
+        };
+        let split_spans = split
+            .iter()
+            .scan(0, |start, token| {
+                let span = *start..*start + token.len();
+                *start = span.end + 1;
+                Some((span, *token))
+            })
+            .filter(|(_, token)| !token.is_empty());
+
+        split_arg_spans(line).chain(split_spans)
+    }
+
+    fn to_line(self) -> String {
+        match self {
+            Tokens::Line(line) => line.to_string(),
+            Tokens::Split(split) => split.join(" "),
+        }
+    }
+}
+
+// The occurrences of the recognized params in a command line.
+struct ParamOccurrences<'a> {
+    // The occurrences of the ordinary params, grouped by normalized name.
+    grouped: BTreeMap<String, Vec<Option<&'a str>>>,
+    // The indices of the tokens of the ordinary params among all the processed
+    // tokens, in the same order as their occurrences.
+    indices: BTreeMap<String, Vec<usize>>,
+    // The occurrences of the prefixed params with the rest of their normalized names.
+    prefixed: Vec<(&'static KernelParam, String, Option<&'a str>)>,
+}
+
+// Splits a token into its name and its optional value.
+//
+// By default, the name and the value are separated by the first `=`. But if
+// the part before the first `:` names a parameter that uses `:` as the
+// separator, the token is split there instead (e.g., `trace:level=debug`).
+//
+// Quotes around the whole token (e.g., `"foo=bar baz"`) and around the value
+// (e.g., `foo="bar baz"`) are stripped.
+fn split_key_value(
+    arg: &str,
+    find_fn: impl Fn(&str) -> Option<&'static KernelParam>,
+) -> (&str, Option<&str>) {
+    let arg = strip_quotes(arg);
+    if let Some((key, value)) = arg.split_once(':')
+        && find_fn(&normalize_name(key)).is_some_and(|param| param.separator == ':')
+    {
+        return (key, Some(strip_quotes(value)));
+    }
+
+    match arg.split_once('=') {
+        Some((key, value)) => (key, Some(strip_quotes(value))),
+        None => (arg, None),
+    }
+}
+
+// Normalizes a parameter name for matching.
+//
+// Like Linux, hyphens and underscores are interchangeable in the names, e.g.,
+// `log-level` matches the parameter registered as `log_level`. The registered
+// names never contain hyphens, so the hyphens are replaced by underscores.
+fn normalize_name(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+// Replaces the value in a token of a sensitive parameter with `****`.
+fn redact_token<'a>(arg: &'a str, registry: &Registry) -> Cow<'a, str> {
+    let find_fn = |name: &str| registry.get(name);
+    let (key, value) = split_key_value(arg, find_fn);
+    match registry.get(&normalize_name(key)) {
+        Some(param) if param.sensitive && value.is_some() => {
+            Cow::Owned(format!("{}{}****", key, param.separator))
+        }
+        _ => Cow::Borrowed(arg),
+    }
+}
+
+// Checks whether a token contains a control character other than whitespace.
+//
+// Such characters are likely garbage from the bootloader, and may break the
+// consumers of the token, e.g., a NUL byte cannot be in a C string.
+fn contains_control_char(arg: &str) -> bool {
+    arg.chars().any(|c| c.is_control() && !c.is_whitespace())
+}
+
+// Strips a pair of matching quotes around a value, e.g. `"hello world"`.
+//
+// The quotes inside the value and the unbalanced ones are kept as is.
+fn strip_quotes(value: &str) -> &str {
+    ['"', '\'']
+        .into_iter()
+        .find_map(|quote| value.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(value)
+}
+
+// Finds the last valid value of a flag before `--`.
+//
+// This is for the flags that apply to the whole command line wherever they appear.
+fn find_flag(tokens: Tokens<'_>, registry: &Registry, flag_name: &str) -> Option<bool> {
+    find_last_valid(tokens, registry, flag_name, |value| {
+        bool::parse_flag(value).ok()
+    })
+}
+
+// Finds the last valid value of `cmdline_loglevel` before `--`.
+fn find_log_level(tokens: Tokens<'_>, registry: &Registry) -> Option<u8> {
+    find_last_valid(tokens, registry, LOG_LEVEL_PARAM_NAME, parse_log_level)
+}
+
+// Finds the last valid value of `numeric_base` before `--`.
+fn find_numeric_base(tokens: Tokens<'_>, registry: &Registry) -> Option<NumericBase> {
+    find_last_valid(tokens, registry, NUMERIC_BASE_PARAM_NAME, |value| {
+        NumericBase::parse_param(value?).ok()
+    })
+}
+
+fn parse_log_level(value: Option<&str>) -> Option<u8> {
+    value
+        .and_then(|value| u8::parse_param(value).ok())
+        .filter(|level| *level <= MAX_LOG_LEVEL)
+}
+
+fn find_last_valid<T>(
+    tokens: Tokens<'_>,
+    registry: &Registry,
+    param_name: &str,
+    parse_fn: impl Fn(Option<&str>) -> Option<T>,
+) -> Option<T> {
+    tokens
+        .spans()
+        .map(|(_, arg)| arg)
+        .take(KernelCmdline::MAX_TOKENS)
+        .take_while(|arg| *arg != "--")
+        .filter_map(|arg| {
+            let (key, value) = split_key_value(arg, |name| registry.get(name));
+            if normalize_name(key) != param_name {
+                return None;
+            }
+            parse_fn(value)
+        })
+        .last()
+}
+
+// Tokenizes a command line and groups the recognized params by normalized name.
+//
+// Unrecognized tokens are forwarded to the init process via `result`.
+fn group_params<'a>(
+    cmdline: Tokens<'a>,
+    registry: &Registry,
+    result: &mut KernelCmdline,
+) -> ParamOccurrences<'a> {
+    let mut kcmdline_end = false;
+    let mut is_strict = false;
+    let is_strict_init = find_flag(cmdline, registry, STRICT_INIT_PARAM_NAME).unwrap_or(false);
+    let forwards_env = find_flag(cmdline, registry, INIT_ENV_PARAM_NAME).unwrap_or(true);
+    let mut max_init_args = usize::MAX;
+    let mut num_init_args = 0usize;
+
+    let mut grouped: BTreeMap<String, Vec<Option<&str>>> = BTreeMap::new();
+    let mut indices: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut prefixed = Vec::new();
+    for (span, arg) in cmdline.spans() {
+        if result.stats.tokens >= KernelCmdline::MAX_TOKENS {
+            result.report(format!(
+                "ignoring the tokens beyond the limit of {} tokens",
+                KernelCmdline::MAX_TOKENS
+            ));
+            break;
+        }
+        if arg.len() > KernelCmdline::MAX_TOKEN_LEN {
+            // The token itself is not reported, since it is too long to be useful.
+            result.report(format!(
+                "ignoring a token longer than {} bytes",
+                KernelCmdline::MAX_TOKEN_LEN
+            ));
+            result.stats.tokens += 1;
+            result.ignore(arg);
+            continue;
+        }
+
+        if contains_control_char(arg) {
+            result.report(format!(
+                "ignoring token {:?} containing a control character",
+                redact_token(arg, registry)
+            ));
+            result.stats.tokens += 1;
+            result.ignore(arg);
+            continue;
+        }
+
+        // Everything after "--" goes to init.
+        if kcmdline_end {
+            result.stats.tokens += 1;
+            if num_init_args < max_init_args {
+                let (key, _) = split_key_value(arg, |name| registry.get(name));
+                if registry.get(&normalize_name(key)).is_some() {
+                    // The kernel parameter may be misplaced after "--" by mistake.
+                    result.report(format!(
+                        "kernel parameter '{}' after '--' is passed to init, not the kernel",
+                        redact_token(arg, registry)
+                    ));
+                }
+                result.forward_arg(arg);
+            } else {
+                result.ignore(arg);
+            }
+            num_init_args = num_init_args.saturating_add(1);
+            continue;
+        }
+        if arg == "--" {
+            kcmdline_end = true;
+            continue;
+        }
+
+        result.stats.tokens += 1;
+
+        let (key, value) = split_key_value(arg, |name| registry.get(name));
+        if key.is_empty() {
+            result.report(format!(
+                "ignoring malformed kernel parameter '{}' at byte {}",
+                arg, span.start
+            ));
+            result.ignore(arg);
+            continue;
+        }
+        let normalized = normalize_name(key);
+
+        match normalized.as_str() {
+            STRICT_PARAM_NAME => {
+                result.take_builtin(arg);
+                match bool::parse_flag(value) {
+                    Ok(strict) => is_strict = strict,
+                    Err(_) => result.report(format!(
+                        "invalid value for kernel parameter '{}'",
+                        STRICT_PARAM_NAME
+                    )),
+                }
+                continue;
+            }
+            STRICT_INIT_PARAM_NAME | INIT_ENV_PARAM_NAME => {
+                // The flags have been looked up before the loop.
+                result.take_builtin(arg);
+                if bool::parse_flag(value).is_err() {
+                    result.report(format!(
+                        "invalid value for kernel parameter '{}'",
+                        normalized
+                    ));
+                }
+                continue;
+            }
+            LOG_LEVEL_PARAM_NAME => {
+                // The level has been looked up before the loop.
+                result.take_builtin(arg);
+                if parse_log_level(value).is_none() {
+                    result.report(format!(
+                        "invalid value for kernel parameter '{}'",
+                        LOG_LEVEL_PARAM_NAME
+                    ));
+                }
+                continue;
+            }
+            NUMERIC_BASE_PARAM_NAME => {
+                // The base has been looked up before the loop.
+                result.take_builtin(arg);
+                if value
+                    .map(NumericBase::parse_param)
+                    .is_none_or(|base| base.is_err())
+                {
+                    result.report(format!(
+                        "invalid value for kernel parameter '{}'",
+                        NUMERIC_BASE_PARAM_NAME
+                    ));
+                }
+                continue;
+            }
+            DUMP_INIT_PARAM_NAME => {
+                result.take_builtin(arg);
+                match bool::parse_flag(value) {
+                    Ok(dumps) => result.dumps_init = dumps,
+                    Err(_) => result.report(format!(
+                        "invalid value for kernel parameter '{}'",
+                        DUMP_INIT_PARAM_NAME
+                    )),
+                }
+                continue;
+            }
+            EXPAND_ENV_PARAM_NAME => {
+                result.take_builtin(arg);
+                match bool::parse_flag(value) {
+                    Ok(expands) => result.expands_init_env = expands,
+                    Err(_) => result.report(format!(
+                        "invalid value for kernel parameter '{}'",
+                        EXPAND_ENV_PARAM_NAME
+                    )),
+                }
+                continue;
+            }
+            DISABLE_PARAM_NAME => {
+                result.take_builtin(arg);
+                match value.map(CommaList::<String>::parse_param) {
+                    Some(Ok(CommaList(names))) => result
+                        .disabled_params
+                        .extend(names.iter().map(|name| normalize_name(name))),
+                    Some(Err(_)) => result.report(format!(
+                        "invalid value for kernel parameter '{}'",
+                        DISABLE_PARAM_NAME
+                    )),
+                    None => result.report(format!(
+                        "kernel parameter '{}' requires a value",
+                        DISABLE_PARAM_NAME
+                    )),
+                }
+                continue;
+            }
+            MAX_INIT_ARGS_PARAM_NAME => {
+                result.take_builtin(arg);
+                match value.map(usize::parse_param) {
+                    Some(Ok(max)) => max_init_args = max,
+                    Some(Err(_)) => result.report(format!(
+                        "invalid value for kernel parameter '{}'",
+                        MAX_INIT_ARGS_PARAM_NAME
+                    )),
+                    None => result.report(format!(
+                        "kernel parameter '{}' requires a value",
+                        MAX_INIT_ARGS_PARAM_NAME
+                    )),
+                }
+                continue;
+            }
+            _ => (),
+        }
+
+        if let Some(param) = registry.get(&normalized) {
+            if !param.allows_empty && value == Some("") {
+                result.report(format!(
+                    "ignoring empty value of kernel parameter '{}'",
+                    arg
+                ));
+                result.ignore(arg);
+                continue;
+            }
+            result.trace(|| format!("dispatching '{}' to kernel parameter '{}'", key, param.name));
+            result.emit(CmdlineEvent::Param { token: arg, param });
+            // Group by normalized name
+            let index = result.stats.tokens - 1;
+            indices.entry(normalized.clone()).or_default().push(index);
+            grouped.entry(normalized).or_default().push(value);
+            if param.passthrough {
+                // The parameter is also meant for the init process.
+                result.forward_param(key, value, forwards_env);
+            } else {
+                result.stats.matched += 1;
+            }
+        } else if let Some((param, suffix)) = registry.find_prefixed(&normalized) {
+            result.trace(|| format!("dispatching '{}' to kernel parameter '{}'", key, param.name));
+            result.emit(CmdlineEvent::Param { token: arg, param });
+            prefixed.push((param, suffix.to_string(), value));
+            result.stats.matched += 1;
+        } else {
+            // Unknown parameter: forward to init
+            if is_strict {
+                // Strict mode rejects unknown parameters instead of forwarding them.
+                result.report(format!("unknown kernel parameter '{}' in strict mode", arg));
+                result.ignore(arg);
+                continue;
+            } else if is_strict_init {
+                // The init arguments must follow "--".
+                result.report(format!(
+                    "unknown kernel parameter '{}' before '--' with {}",
+                    arg, STRICT_INIT_PARAM_NAME
+                ));
+                result.ignore(arg);
+                continue;
+            } else if key.contains('.') {
+                // The entry contains a dot, which is treated as a module argument.
+                // The registered modules have been tried by `find_prefixed`, so
+                // the module argument is unrecognized and is ignored.
+                if let Some((head, _)) = normalized.split_once('.')
+                    && registry.get(head).is_some()
+                {
+                    // A known parameter with a stray dot is likely a typo.
+                    result.report(format!(
+                        "ignoring unknown module parameter '{}'; did you mean '{}'?",
+                        arg, head
+                    ));
+                } else {
+                    result.report(format!("ignoring unknown module parameter '{}'", arg));
+                }
+                result
+                    .dropped_module_params
+                    .push((key.to_string(), value.map(ToString::to_string)));
+                result.ignore(arg);
+                continue;
+            } else {
+                // If the entry is not recognized, it is passed to the init process.
+                result.forward_param(key, value, forwards_env);
+            }
+        }
+    }
+
+    if num_init_args > max_init_args {
+        result.report(format!(
+            "dropping {} init arguments beyond the limit of {}",
+            num_init_args - max_init_args,
+            max_init_args
+        ));
+    }
+
+    ParamOccurrences {
+        grouped,
+        indices,
+        prefixed,
+    }
+}
+
+#[cfg(ktest)]
+mod tests {
+    use alloc::vec;
+
+    use ostd::prelude::*;
+
+    use super::*;
+
+    fn dispatch_params(cmdline: &str) -> KernelCmdline {
+        dispatch_params_from_parts(Tokens::Line(cmdline), "")
+    }
+
+    #[ktest]
+    fn unknown_kv_forwarded_to_init_env() {
+        let cmdline = dispatch_params("unknown_key=1");
+        let args = cmdline.init_proc_args();
+        assert!(args.envp().iter().any(|e| e.to_bytes() == b"unknown_key=1"));
+    }
+
+    #[ktest]
+    fn dotted_unknown_param_not_forwarded() {
+        let cmdline = dispatch_params("some_module.flag");
+        let args = cmdline.init_proc_args();
+        assert!(args.argv().is_empty());
+        assert!(args.envp().is_empty());
+    }
+
+    #[ktest]
+    fn repeated_unknown_kv_and_arg_forwarding() {
+        let cmdline = dispatch_params("unknown_key=1 unknown_key unknown_key=2");
+        let args = cmdline.init_proc_args();
+
+        assert_eq!(args.envp.len(), 2);
+        assert_eq!(args.envp[0].to_bytes(), b"unknown_key=1");
+        assert_eq!(args.envp[1].to_bytes(), b"unknown_key=2");
+
+        assert_eq!(args.argv.len(), 1);
+        assert_eq!(args.argv[0].to_bytes(), b"unknown_key");
+    }
+
+    #[ktest]
+    fn registered_params_not_forwarded() {
+        static TEST_VARIABLE: Once<u32> = Once::new();
+        static TEST_VARIABLE_REPEATED: Once<Vec<String>> = Once::new();
+
+        crate::define_kv_param!("log_level", TEST_VARIABLE);
+        crate::define_repeatable_kv_param!("console", TEST_VARIABLE_REPEATED);
+
+        let cmdline = dispatch_params("log_level=4 console=ttyS0 console=ttyS1");
+        let args = cmdline.init_proc_args();
+        assert!(args.argv().is_empty());
+        assert!(args.envp().is_empty());
+    }
+
+    #[ktest]
+    fn malformed_param_recorded_in_diagnostics() {
+        let cmdline = dispatch_params("unknown_key=1  ===");
+
+        assert_eq!(
+            cmdline.diagnostics(),
+            ["ignoring malformed kernel parameter '===' at byte 15"]
+        );
+
+        let args = cmdline.init_proc_args();
+        assert!(args.argv().is_empty());
+        assert_eq!(args.envp().len(), 1);
+    }
+
+    #[ktest]
+    fn strict_flag_applies_to_subsequent_params() {
+        let cmdline =
+            dispatch_params("lenient_arg lenient_key=1 cmdline_strict strict_arg strict_key=2");
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 1);
+        assert_eq!(args.argv()[0].to_bytes(), b"lenient_arg");
+        assert_eq!(args.envp().len(), 1);
+        assert_eq!(args.envp()[0].to_bytes(), b"lenient_key=1");
+
+        assert_eq!(cmdline.diagnostics().len(), 2);
+        assert!(cmdline.diagnostics()[0].contains("strict_arg"));
+        assert!(cmdline.diagnostics()[1].contains("strict_key=2"));
+    }
+
+    #[ktest]
+    fn strict_flag_can_be_turned_off() {
+        let cmdline = dispatch_params("cmdline_strict=1 strict_arg cmdline_strict=0 lenient_arg");
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 1);
+        assert_eq!(args.argv()[0].to_bytes(), b"lenient_arg");
+        assert_eq!(cmdline.diagnostics().len(), 1);
+    }
+
+    #[ktest]
+    fn strict_flag_not_applied_after_separator() {
+        let cmdline = dispatch_params("cmdline_strict -- init_arg");
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 1);
+        assert_eq!(args.argv()[0].to_bytes(), b"init_arg");
+        assert!(cmdline.diagnostics().is_empty());
+    }
+
+    #[ktest]
+    fn split_arg_single_quotes() {
+        let args: Vec<_> = split_arg("a='x y' b").collect();
+        assert_eq!(args, ["a='x y'", "b"]);
+    }
+
+    #[ktest]
+    fn split_arg_quote_inside_other_quotes() {
+        let args: Vec<_> = split_arg("a=\"x ' y\" b").collect();
+        assert_eq!(args, ["a=\"x ' y\"", "b"]);
+
+        let args: Vec<_> = split_arg("a='x \" y' b").collect();
+        assert_eq!(args, ["a='x \" y'", "b"]);
+    }
+
+    #[ktest]
+    fn split_arg_unbalanced_mixed_quotes() {
+        let args: Vec<_> = split_arg("a='x\" y b").collect();
+        assert_eq!(args, ["a='x\" y b"]);
+
+        let args: Vec<_> = split_arg("a=\"x' y\" b='c d").collect();
+        assert_eq!(args, ["a=\"x' y\"", "b='c d"]);
+    }
+
+    #[ktest]
+    fn split_arg_skips_empty_tokens() {
+        let args: Vec<_> = split_arg("  a   b\n").collect();
+        assert_eq!(args, ["a", "b"]);
+    }
+
+    #[ktest]
+    fn builtin_cmdline_fills_missing_params() {
+        static TEST_BOOTLOADER_ONLY: Once<u32> = Once::new();
+        static TEST_BOTH: Once<u32> = Once::new();
+        static TEST_BUILTIN_ONLY: Once<u32> = Once::new();
+
+        crate::define_kv_param!("test_bootloader_only", TEST_BOOTLOADER_ONLY);
+        crate::define_kv_param!("test_both", TEST_BOTH);
+        crate::define_kv_param!("test_builtin_only", TEST_BUILTIN_ONLY);
+
+        let cmdline = dispatch_params_from_parts(
+            Tokens::Line("test_bootloader_only=1 test_both=2 boot_arg"),
+            "test_both=3 test_builtin_only=4 builtin_arg",
+        );
+
+        assert_eq!(TEST_BOOTLOADER_ONLY.get(), Some(&1));
+        assert_eq!(TEST_BOTH.get(), Some(&2));
+        assert_eq!(TEST_BUILTIN_ONLY.get(), Some(&4));
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 2);
+        assert_eq!(args.argv()[0].to_bytes(), b"boot_arg");
+        assert_eq!(args.argv()[1].to_bytes(), b"builtin_arg");
+    }
+
+    #[ktest]
+    fn builtin_cmdline_not_affected_by_bootloader_separator() {
+        let cmdline = dispatch_params_from_parts(Tokens::Line("-- init_arg"), "builtin_key=1");
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 1);
+        assert_eq!(args.argv()[0].to_bytes(), b"init_arg");
+        assert_eq!(args.envp().len(), 1);
+        assert_eq!(args.envp()[0].to_bytes(), b"builtin_key=1");
+    }
+
+    #[ktest]
+    fn resolve_known_unimplemented_and_unknown() {
+        static TEST_RESOLVE_KNOWN: Once<u32> = Once::new();
+
+        crate::define_kv_param!("test_resolve_known", TEST_RESOLVE_KNOWN);
+        crate::define_unimplemented_param!("test_resolve_unimplemented");
+
+        let ResolveResult::Known(param) = resolve("test_resolve_known=1") else {
+            panic!("`test_resolve_known` should be a known parameter");
+        };
+        assert_eq!(param.name(), "test_resolve_known");
+        assert!(matches!(
+            resolve("test-resolve-known"),
+            ResolveResult::Known(_)
+        ));
+
+        let ResolveResult::Unimplemented(param) = resolve("test_resolve_unimplemented") else {
+            panic!("`test_resolve_unimplemented` should be an unimplemented parameter");
+        };
+        assert_eq!(param.name(), "test_resolve_unimplemented");
+
+        assert!(matches!(
+            resolve("test_resolve_unknown=1"),
+            ResolveResult::Unknown
+        ));
+    }
+
+    #[ktest]
+    fn higher_priority_params_set_up_first() {
+        struct OrderRecorder(&'static str);
+
+        static SETUP_ORDER: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+        impl crate::parse::ParamStorage for OrderRecorder {
+            type Value = bool;
+            fn store_param(&self, _value: bool) {
+                SETUP_ORDER.lock().push(self.0);
+            }
+        }
+
+        static TEST_PRIORITY_A_LOW: OrderRecorder = OrderRecorder("test_priority_a_low");
+        static TEST_PRIORITY_B_HIGH: OrderRecorder = OrderRecorder("test_priority_b_high");
+        static TEST_PRIORITY_C_DEFAULT: OrderRecorder = OrderRecorder("test_priority_c_default");
+
+        crate::define_flag_param_early!("test_priority_a_low", TEST_PRIORITY_A_LOW, priority = 1);
+        crate::define_flag_param_early!(
+            "test_priority_b_high",
+            TEST_PRIORITY_B_HIGH,
+            priority = 200
+        );
+        crate::define_flag_param_early!("test_priority_c_default", TEST_PRIORITY_C_DEFAULT);
+
+        dispatch_params("test_priority_a_low test_priority_b_high test_priority_c_default");
+
+        assert_eq!(
+            *SETUP_ORDER.lock(),
+            [
+                "test_priority_b_high",
+                "test_priority_c_default",
+                "test_priority_a_low"
+            ]
+        );
+    }
+
+    #[ktest]
+    fn max_init_args_truncates_init_argv() {
+        let cmdline = dispatch_params("max_init_args=2 boot_arg -- arg1 arg2 arg3 arg4");
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 3);
+        assert_eq!(args.argv()[0].to_bytes(), b"boot_arg");
+        assert_eq!(args.argv()[1].to_bytes(), b"arg1");
+        assert_eq!(args.argv()[2].to_bytes(), b"arg2");
+
+        assert_eq!(cmdline.diagnostics().len(), 1);
+        assert!(cmdline.diagnostics()[0].contains("dropping 2 init arguments"));
+    }
+
+    #[ktest]
+    fn max_init_args_within_limit() {
+        let cmdline = dispatch_params("max_init_args=2 -- arg1 arg2");
+
+        assert_eq!(cmdline.init_proc_args().argv().len(), 2);
+        assert!(cmdline.diagnostics().is_empty());
+    }
+
+    #[ktest]
+    fn max_init_args_after_separator_not_applied() {
+        let cmdline = dispatch_params("-- arg1 max_init_args=0 arg2");
+
+        assert_eq!(cmdline.init_proc_args().argv().len(), 3);
+        assert!(cmdline.diagnostics().is_empty());
+    }
+
+    #[ktest]
+    fn max_init_args_invalid_value() {
+        let cmdline = dispatch_params("max_init_args=x max_init_args -- arg1");
+
+        assert_eq!(cmdline.init_proc_args().argv().len(), 1);
+        assert_eq!(cmdline.diagnostics().len(), 2);
+    }
+
+    #[ktest]
+    fn colon_separated_param_routed() {
+        static TEST_COLON: Once<String> = Once::new();
+
+        crate::define_colon_kv_param!("test_colon", TEST_COLON);
+
+        let cmdline = dispatch_params("test_colon:level=debug other:value other=1");
+
+        assert_eq!(TEST_COLON.get().map(String::as_str), Some("level=debug"));
+        assert!(matches!(
+            resolve("test-colon:level=debug"),
+            ResolveResult::Known(_)
+        ));
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 1);
+        assert_eq!(args.argv()[0].to_bytes(), b"other:value");
+        assert_eq!(args.envp().len(), 1);
+        assert_eq!(args.envp()[0].to_bytes(), b"other=1");
+    }
+
+    #[ktest]
+    fn stats_count_tokens_by_outcome() {
+        static TEST_STATS: Once<u32> = Once::new();
+
+        crate::define_kv_param!("test_stats", TEST_STATS);
+
+        let cmdline = dispatch_params(
+            "test_stats=1 max_init_args=1 init_key=1 init_arg some_module.flag =x -- arg1 arg2",
+        );
+
+        assert_eq!(
+            cmdline.stats(),
+            CmdlineStats {
+                tokens: 8,
+                matched: 2,
+                forwarded: 3,
+                ignored: 3,
+            }
+        );
+    }
+
+    #[ktest]
+    fn try_from_cmdline_ok() {
+        let cmdline = KernelCmdline::try_from_cmdline("init_arg init_key=1 -- arg1 =x").unwrap();
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 3);
+        assert_eq!(args.envp().len(), 1);
+    }
+
+    #[ktest]
+    fn try_from_cmdline_malformed_token() {
+        assert_eq!(
+            KernelCmdline::try_from_cmdline("init_arg =x").unwrap_err(),
+            CmdlineError::MalformedToken("=x")
+        );
+    }
+
+    #[ktest]
+    fn try_from_cmdline_nul_in_value() {
+        assert_eq!(
+            KernelCmdline::try_from_cmdline("init_key=a\0b").unwrap_err(),
+            CmdlineError::NulInValue
+        );
+        assert_eq!(
+            KernelCmdline::try_from_cmdline("-- a\0b").unwrap_err(),
+            CmdlineError::NulInValue
+        );
+    }
+
+    #[ktest]
+    fn nul_in_init_args_dropped_without_panic() {
+        let cmdline = dispatch_params("init_a\0rg init_key=a\0b init_arg -- a\0b arg1");
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 2);
+        assert_eq!(args.argv()[0].to_bytes(), b"init_arg");
+        assert_eq!(args.argv()[1].to_bytes(), b"arg1");
+        assert!(args.envp().is_empty());
+
+        assert_eq!(cmdline.diagnostics().len(), 3);
+        assert_eq!(cmdline.stats().ignored, 3);
+    }
+
+    #[ktest]
+    fn init_env_refs_expanded_when_enabled() {
+        let cmdline = dispatch_params("init_expand_env FOO=bar -- echo $FOO $ $FOO/bin x$FOO.y");
+
+        let argv: Vec<_> = cmdline.init_proc_args().argv_str().collect();
+        assert_eq!(argv, ["echo", "bar", "$", "bar/bin", "xbar.y"]);
+        assert!(cmdline.diagnostics().is_empty());
+    }
+
+    #[ktest]
+    fn init_env_braced_refs_expanded() {
+        let cmdline = dispatch_params("init_expand_env FOO=bar -- ${FOO}baz ${FOO ${}");
+
+        let argv: Vec<_> = cmdline.init_proc_args().argv_str().collect();
+        assert_eq!(argv, ["barbaz", "${FOO", "${}"]);
+    }
+
+    #[ktest]
+    fn init_env_refs_to_earlier_env_expanded() {
+        let cmdline = dispatch_params("init_expand_env PATH=$FOO/bin FOO=bar PATH=$FOO/bin");
+
+        let envp: Vec<_> = cmdline.init_proc_args().envp_str().collect();
+        assert_eq!(envp, ["PATH=$FOO/bin", "FOO=bar", "PATH=bar/bin"]);
+        assert_eq!(
+            cmdline.diagnostics(),
+            ["init argument references undefined variable 'FOO'"]
+        );
+    }
+
+    #[ktest]
+    fn init_env_undefined_refs_kept() {
+        let cmdline = dispatch_params("init_expand_env FOO=bar -- $UNSET ${FOOBAR}");
+
+        let argv: Vec<_> = cmdline.init_proc_args().argv_str().collect();
+        assert_eq!(argv, ["$UNSET", "${FOOBAR}"]);
+        assert_eq!(cmdline.diagnostics().len(), 2);
+    }
+
+    #[ktest]
+    fn init_env_refs_not_expanded_by_default() {
+        let cmdline = dispatch_params("FOO=bar -- echo $FOO");
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv().len(), 2);
+        assert_eq!(args.argv()[1].to_bytes(), b"$FOO");
+    }
+
+    #[ktest]
+    fn module_params_reach_handler() {
+        static RECEIVED: Mutex<Vec<(String, Option<String>)>> = Mutex::new(Vec::new());
+
+        fn setup_test_e1000(suffix: &str, value: Option<&str>) -> Result<(), ParamError> {
+            RECEIVED
+                .lock()
+                .push((suffix.to_string(), value.map(str::to_string)));
+            Ok(())
+        }
+
+        crate::define_kernel_param_module!("test_e1000", setup_test_e1000);
+
+        let param = registered_params()
+            .find(|param| param.name() == "test_e1000")
+            .unwrap();
+        assert_eq!(param.matches_prefix("test_e1000.debug"), Some("debug"));
+        assert_eq!(param.matches_prefix("test_e1000"), None);
+        assert_eq!(param.matches_prefix("test_e1000x.debug"), None);
+        assert_eq!(param.to_string(), "test_e1000.* (late)");
+
+        let cmdline = dispatch_params("test_e1000.debug=1 test_e1000x.debug=2 test_e1000");
+        assert_eq!(
+            *RECEIVED.lock(),
+            [("debug".to_string(), Some("1".to_string()))]
+        );
+        assert_eq!(
+            cmdline.dropped_module_params().collect::<Vec<_>>(),
+            [("test_e1000x.debug", Some("2"))]
+        );
+        assert_eq!(
+            cmdline.effective_params().collect::<Vec<_>>(),
+            [("test_e1000.debug", Some("1"))]
+        );
+        assert_eq!(
+            cmdline.init_proc_args().argv_str().collect::<Vec<_>>(),
+            ["test_e1000"]
+        );
+    }
+
+    #[ktest]
+    fn prefixed_params_reach_handler() {
+        static RECEIVED: Mutex<Vec<(String, Option<String>)>> = Mutex::new(Vec::new());
+
+        fn setup_test_prefix(suffix: &str, value: Option<&str>) -> Result<(), ParamError> {
+            RECEIVED
+                .lock()
+                .push((suffix.to_string(), value.map(str::to_string)));
+            Ok(())
+        }
+
+        crate::define_prefix_param!("test_prefix.", setup_test_prefix);
+
+        let cmdline = dispatch_params("test_prefix.foo=1 test-prefix.bar other_prefix.baz");
+
+        assert_eq!(
+            *RECEIVED.lock(),
+            [
+                ("foo".to_string(), Some("1".to_string())),
+                ("bar".to_string(), None)
+            ]
+        );
+        assert!(matches!(
+            resolve("test_prefix.qux"),
+            ResolveResult::Known(_)
+        ));
+
+        assert_eq!(cmdline.stats().matched, 2);
+        assert_eq!(cmdline.stats().ignored, 1);
+    }
+
+    #[ktest]
+    fn numeric_base_hex_applies_to_hex_ints() {
+        use crate::types::HexInt;
+
+        static TEST_NUMERIC_BASE_FOO: Once<HexInt<u32>> = Once::new();
+        crate::define_kv_param!("test_numeric_base_foo", TEST_NUMERIC_BASE_FOO);
+
+        static TEST_NUMERIC_BASE_EARLY: Once<HexInt<u32>> = Once::new();
+        crate::define_kv_param_early!(
+            "test_numeric_base_early",
+            TEST_NUMERIC_BASE_EARLY,
+            priority = u8::MAX
+        );
+
+        let cmdline =
+            dispatch_params("test_numeric_base_early=10 test_numeric_base_foo=ff numeric_base=hex");
+
+        assert!(cmdline.diagnostics().is_empty());
+        assert_eq!(TEST_NUMERIC_BASE_FOO.get(), Some(&HexInt(255)));
+        assert_eq!(TEST_NUMERIC_BASE_EARLY.get(), Some(&HexInt(16)));
+
+        let cmdline = dispatch_params("numeric_base=oct");
+        assert_eq!(
+            cmdline.diagnostics(),
+            ["invalid value for kernel parameter 'numeric_base'"]
+        );
+    }
+
+    #[ktest]
+    fn numeric_base_not_carried_over() {
+        use crate::{parse::ParseParamValue, types::HexInt};
+
+        static TEST_NUMERIC_BASE_BAR: Mutex<Option<HexInt<u32>>> = Mutex::new(None);
+        crate::define_kv_param!("test_numeric_base_bar", TEST_NUMERIC_BASE_BAR);
+
+        let mut cmdline = dispatch_params("numeric_base=hex test_numeric_base_bar=10");
+        assert_eq!(*TEST_NUMERIC_BASE_BAR.lock(), Some(HexInt(16)));
+        assert_eq!(HexInt::<u32>::parse_param("10").unwrap(), HexInt(10));
+
+        cmdline.extend_from("test_numeric_base_bar=10");
+        assert_eq!(*TEST_NUMERIC_BASE_BAR.lock(), Some(HexInt(10)));
+    }
+
+    #[ktest]
+    fn unimplemented_param_passthrough() {
+        crate::define_unimplemented_param!("test_unimpl_consumed");
+        crate::define_unimplemented_param!(
+            passthrough = true,
+            "test_unimpl_passed",
+            "test_unimpl_passed_kv"
+        );
+
+        let cmdline =
+            dispatch_params("test_unimpl_consumed=1 test_unimpl_passed test_unimpl_passed_kv=2");
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv(), &[CString::from(c"test_unimpl_passed")]);
+        assert_eq!(args.envp(), &[CString::from(c"test_unimpl_passed_kv=2")]);
+        assert_eq!(
+            cmdline.stats(),
+            CmdlineStats {
+                tokens: 3,
+                matched: 1,
+                forwarded: 2,
+                ignored: 0,
+            }
+        );
+    }
+
+    #[ktest]
+    fn param_count_and_has_unimplemented() {
+        static TEST_PARAM_COUNT_FLAG: Once<bool> = Once::new();
+        static TEST_PARAM_COUNT_KV: Once<u32> = Once::new();
+        crate::define_flag_param!("test_param_count_flag", TEST_PARAM_COUNT_FLAG);
+        crate::define_kv_param!("test_param_count_kv", TEST_PARAM_COUNT_KV);
+        crate::define_unimplemented_param!("test_param_count_unimpl");
+
+        let cmdline = dispatch_params(
+            "test_param_count_flag test_param_count_kv=1 test_param_count_flag=0 unknown",
+        );
+        assert_eq!(cmdline.param_count(), 2);
+        assert!(!cmdline.has_unimplemented());
+
+        let cmdline = dispatch_params("test_param_count_unimpl test_param_count_kv=2");
+        assert_eq!(cmdline.param_count(), 2);
+        assert!(cmdline.has_unimplemented());
+
+        let cmdline = dispatch_params("unknown -- test_param_count_unimpl");
+        assert_eq!(cmdline.param_count(), 0);
+        assert!(!cmdline.has_unimplemented());
+    }
+
+    #[ktest]
+    fn strict_init_rejects_init_args_before_separator() {
+        let cmdline = dispatch_params("init_arg init_key=1 strict_init -- after_arg");
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv(), &[CString::from(c"after_arg")]);
+        assert!(args.envp().is_empty());
+        assert_eq!(cmdline.diagnostics().len(), 2);
+        assert!(cmdline.diagnostics()[0].contains("init_arg"));
+        assert!(cmdline.diagnostics()[1].contains("init_key=1"));
+    }
+
+    #[ktest]
+    fn init_args_before_separator_forwarded_without_strict_init() {
+        for cmdline in [
+            "init_arg -- after_arg",
+            "init_arg strict_init=0 -- after_arg",
+        ] {
+            let cmdline = dispatch_params(cmdline);
+            let args = cmdline.init_proc_args();
+            assert_eq!(
+                args.argv(),
+                &[CString::from(c"init_arg"), CString::from(c"after_arg")]
+            );
+            assert!(cmdline.diagnostics().is_empty());
+        }
+    }
+
+    #[ktest]
+    fn strict_init_after_separator_ignored() {
+        let cmdline = dispatch_params("init_arg -- strict_init");
+        let args = cmdline.init_proc_args();
+        assert_eq!(
+            args.argv(),
+            &[CString::from(c"init_arg"), CString::from(c"strict_init")]
+        );
+    }
+
+    #[ktest]
+    fn kv_param_last_occurrence_wins() {
+        static TEST_LOGLEVEL: Once<u32> = Once::new();
+        static TEST_LOGLEVEL_OVERWRITTEN: Mutex<Option<u32>> = Mutex::new(None);
+        crate::define_kv_param!("test_loglevel", TEST_LOGLEVEL);
+        crate::define_kv_param!("test_loglevel_overwritten", TEST_LOGLEVEL_OVERWRITTEN);
+
+        dispatch_params("test_loglevel=3 test_loglevel=5");
+        assert_eq!(TEST_LOGLEVEL.get(), Some(&5));
+
+        dispatch_params("test_loglevel_overwritten=3 test_loglevel_overwritten=5");
+        assert_eq!(*TEST_LOGLEVEL_OVERWRITTEN.lock(), Some(5));
+        dispatch_params("test_loglevel_overwritten=7");
+        assert_eq!(*TEST_LOGLEVEL_OVERWRITTEN.lock(), Some(7));
+    }
+
+    #[ktest]
+    fn token_count_limited() {
+        let cmdline = "t ".repeat(10_000);
+        let cmdline = dispatch_params(&cmdline);
+        assert_eq!(cmdline.stats().tokens, KernelCmdline::MAX_TOKENS);
+        assert_eq!(
+            cmdline.init_proc_args().argv().len(),
+            KernelCmdline::MAX_TOKENS
+        );
+        assert_eq!(cmdline.diagnostics().len(), 1);
+        assert!(cmdline.diagnostics()[0].contains("limit"));
+    }
+
+    #[ktest]
+    fn overlong_token_skipped() {
+        let cmdline = "a".repeat(KernelCmdline::MAX_TOKEN_LEN + 1) + " short";
+        let cmdline = dispatch_params(&cmdline);
+        assert_eq!(cmdline.init_proc_args().argv(), &[CString::from(c"short")]);
+        assert_eq!(cmdline.stats().ignored, 1);
+        assert_eq!(cmdline.diagnostics().len(), 1);
+    }
+
+    #[ktest]
+    fn init_args_dump_lists_each_entry() {
+        let cmdline = dispatch_params("dump_init HOME=/ TERM=vt100 -- sh -l");
+        assert!(cmdline.diagnostics().is_empty());
+        assert_eq!(cmdline.stats().matched, 1);
+        assert_eq!(
+            cmdline.init_proc_args().to_string(),
+            "argv[0]: sh\nargv[1]: -l\nenvp[0]: HOME=/\nenvp[1]: TERM=vt100\n"
+        );
+    }
+
+    #[ktest]
+    fn value_parser_sees_param_name() {
+        use crate::parse::ParseParamValue;
+
+        #[derive(Debug, PartialEq)]
+        struct NamedValue(&'static str, u32);
+
+        impl ParseParamValue for NamedValue {
+            fn parse_param(_value: &str) -> Result<Self, ParamError> {
+                Err(ParamError::InvalidValue)
+            }
+
+            fn parse_named_param(name: &'static str, value: &str) -> Result<Self, ParamError> {
+                let value = u32::parse_param(value)?;
+                Ok(NamedValue(name, value))
+            }
+        }
+
+        static TEST_NAMED_VALUE: Once<NamedValue> = Once::new();
+        crate::define_kv_param!("test_named_value", TEST_NAMED_VALUE);
+
+        dispatch_params("test-named-value=3");
+        assert_eq!(
+            TEST_NAMED_VALUE.get(),
+            Some(&NamedValue("test_named_value", 3))
+        );
+    }
+
+    #[ktest]
+    fn value_notes_reported_as_diagnostics() {
+        crate::kernel_param_capmask! {
+            struct TestNotedCaps {
+                "cap_a" => 0,
+            }
+        }
+
+        static TEST_NOTED_CAPS: Once<TestNotedCaps> = Once::new();
+        crate::define_kv_param!("test_noted_caps", TEST_NOTED_CAPS);
+
+        let cmdline = dispatch_params("test_noted_caps=cap_a,cap_x");
+        assert_eq!(TEST_NOTED_CAPS.get(), Some(&TestNotedCaps(0b1)));
+        assert_eq!(
+            cmdline.diagnostics(),
+            ["ignoring unknown capability 'cap_x' of kernel parameter 'test_noted_caps'"]
+        );
+
+        let cmdline = dispatch_params("cmdline_loglevel=0 test_noted_caps=cap_x");
+        assert!(cmdline.diagnostics().is_empty());
+    }
+
+    #[ktest]
+    fn clamped_value_recorded_in_diagnostics() {
+        use crate::types::Clamp;
+
+        static TEST_CLAMPED: Mutex<Option<Clamp<0, 7>>> = Mutex::new(None);
+        crate::define_kv_param!("test_clamped", TEST_CLAMPED);
+
+        let cmdline = dispatch_params("test_clamped=99");
+        assert_eq!(*TEST_CLAMPED.lock(), Some(Clamp(7)));
+        assert_eq!(
+            cmdline.diagnostics(),
+            ["clamping value '99' of kernel parameter 'test_clamped' to 7"]
+        );
+
+        let cmdline = dispatch_params("test_clamped=3");
+        assert_eq!(*TEST_CLAMPED.lock(), Some(Clamp(3)));
+        assert!(cmdline.diagnostics().is_empty());
+    }
+
+    #[ktest]
+    fn diagnostics_of_broken_cmdline() {
+        static TEST_BROKEN_NUMBER: Once<u32> = Once::new();
+        crate::define_kv_param!("test_broken_number", TEST_BROKEN_NUMBER);
+        crate::define_unimplemented_param!("test_broken_unimpl");
+
+        let cmdline = dispatch_params("=1 test_broken_number=abc test_broken_unimpl");
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "ignoring malformed kernel parameter '=1' at byte 0",
+                "invalid value 'abc' for kernel parameter 'test_broken_number'",
+                "kernel parameter 'test_broken_unimpl' is not yet implemented",
+            ]
+        );
+    }
+
+    #[ktest]
+    fn extend_from_merges_fragments() {
+        static TEST_EXTEND_FIRST: Once<u32> = Once::new();
+        static TEST_EXTEND_SECOND: Once<u32> = Once::new();
+        crate::define_kv_param!("test_extend_first", TEST_EXTEND_FIRST);
+        crate::define_kv_param!("test_extend_second", TEST_EXTEND_SECOND);
+
+        let mut cmdline = dispatch_params("test_extend_first=1 A=1 -- arg1 arg2");
+        cmdline.extend_from("test_extend_second=2 B=2 -- arg3 -- arg4");
+
+        assert_eq!(TEST_EXTEND_FIRST.get(), Some(&1));
+        assert_eq!(TEST_EXTEND_SECOND.get(), Some(&2));
+        assert_eq!(cmdline.param_count(), 2);
+
+        let args = cmdline.init_proc_args();
+        assert_eq!(
+            args.argv(),
+            &[
+                CString::from(c"arg1"),
+                CString::from(c"arg2"),
+                CString::from(c"arg3"),
+                CString::from(c"--"),
+                CString::from(c"arg4"),
+            ]
+        );
+        assert_eq!(args.envp(), &[CString::from(c"A=1"), CString::from(c"B=2")]);
+    }
+
+    #[ktest]
+    fn quotes_around_values_stripped() {
+        static TEST_QUOTED_DOUBLE: Once<String> = Once::new();
+        static TEST_QUOTED_SINGLE: Once<String> = Once::new();
+        static TEST_QUOTED_NONE: Once<String> = Once::new();
+        static TEST_QUOTED_PARTIAL: Once<String> = Once::new();
+        static TEST_QUOTED_INNER: Once<String> = Once::new();
+        crate::define_kv_param!("test_quoted_double", TEST_QUOTED_DOUBLE);
+        crate::define_kv_param!("test_quoted_single", TEST_QUOTED_SINGLE);
+        crate::define_kv_param!("test_quoted_none", TEST_QUOTED_NONE);
+        crate::define_kv_param!("test_quoted_partial", TEST_QUOTED_PARTIAL);
+        crate::define_kv_param!("test_quoted_inner", TEST_QUOTED_INNER);
+
+        let cmdline = dispatch_params(concat!(
+            "test_quoted_double=\"hello world\" test_quoted_single='a b' ",
+            "test_quoted_none=plain test_quoted_inner=a\"b\"c msg=\"x y\" ",
+            // An unbalanced quote extends to the end of the command line.
+            "test_quoted_partial=\"open"
+        ));
+
+        assert_eq!(TEST_QUOTED_DOUBLE.get().unwrap(), "hello world");
+        assert_eq!(TEST_QUOTED_SINGLE.get().unwrap(), "a b");
+        assert_eq!(TEST_QUOTED_NONE.get().unwrap(), "plain");
+        assert_eq!(TEST_QUOTED_PARTIAL.get().unwrap(), "\"open");
+        assert_eq!(TEST_QUOTED_INNER.get().unwrap(), "a\"b\"c");
+        assert_eq!(
+            cmdline.init_proc_args().envp(),
+            &[CString::from(c"msg=x y")]
+        );
     }
 
-    /// Returns the environment vector (`envp`) of the init process.
-    pub fn envp(&self) -> &[CString] {
-        &self.envp
+    #[ktest]
+    fn dotted_known_param_suggested() {
+        static TEST_QUIET: Once<bool> = Once::new();
+        crate::define_flag_param!("test_quiet", TEST_QUIET);
+
+        let cmdline = dispatch_params("test_quiet. test-quiet.x=1 test_unknown.x");
+        assert!(TEST_QUIET.get().is_none());
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "ignoring unknown module parameter 'test_quiet.'; did you mean 'test_quiet'?",
+                "ignoring unknown module parameter 'test-quiet.x=1'; did you mean 'test_quiet'?",
+                "ignoring unknown module parameter 'test_unknown.x'",
+            ]
+        );
     }
-}
 
-#[doc(hidden)]
-#[derive(Debug)]
-pub struct KernelParam {
-    name: &'static str,
-    setup_fn: fn(occurrences: &[Option<&str>]),
-    early: bool,
-}
+    #[ktest]
+    fn validators_run_after_setup() {
+        static TEST_ORDER_MIN: Mutex<Option<u32>> = Mutex::new(None);
+        static TEST_ORDER_MAX: Mutex<Option<u32>> = Mutex::new(None);
+        crate::define_kv_param!("test_order_min", TEST_ORDER_MIN);
+        crate::define_kv_param!("test_order_max", TEST_ORDER_MAX);
 
-impl KernelParam {
-    #[doc(hidden)]
-    pub const fn new(
-        name: &'static str,
-        setup_fn: fn(occurrences: &[Option<&str>]),
-        early: bool,
-    ) -> KernelParam {
-        if Self::contains_hyphen(name) {
-            panic!("kernel param registration must not contain '-' (use '_')");
+        fn check_test_order() -> Result<(), &'static str> {
+            match (*TEST_ORDER_MIN.lock(), *TEST_ORDER_MAX.lock()) {
+                (Some(min), Some(max)) if min > max => {
+                    Err("test_order_min is greater than test_order_max")
+                }
+                _ => Ok(()),
+            }
         }
-        KernelParam {
-            name,
-            setup_fn,
-            early,
+
+        crate::register_cmdline_validator!(check_test_order);
+
+        let mut cmdline = dispatch_params("test_order_min=1 test_order_max=2");
+        assert!(cmdline.diagnostics().is_empty());
+        assert_eq!(*TEST_ORDER_MIN.lock(), Some(1));
+
+        // Other tests run the registered validators, so the failing one is not
+        // registered.
+        fn fail_always() -> Result<(), &'static str> {
+            Err("always fails")
         }
+        let validator = CmdlineValidator::new("fail_always", fail_always);
+        cmdline.run_validators([&validator]);
+        assert_eq!(
+            cmdline.diagnostics(),
+            ["kernel parameter validator 'fail_always' failed: always fails"]
+        );
     }
 
-    const fn contains_hyphen(s: &'static str) -> bool {
-        let bytes = s.as_bytes();
-        let mut i = 0;
-        while i < bytes.len() {
-            if bytes[i] == b'-' {
-                return true;
-            }
-            i += 1;
+    #[ktest]
+    fn missing_required_params_listed() {
+        static TEST_REQUIRED_PRESENT: Once<u32> = Once::new();
+        crate::define_kv_param!("test_required_present", TEST_REQUIRED_PRESENT);
+
+        fn setup_nothing(
+            _occurrences: &[Option<&str>],
+            _notes: &mut ParamNotes,
+        ) -> Result<(), ParamError> {
+            Ok(())
         }
-        false
+        // A required parameter submitted to the registry would fail the boot
+        // of the test kernel, so the descriptors are checked directly.
+        static PRESENT: KernelParam =
+            KernelParam::new("test_required_present", setup_nothing, false).with_required(true);
+        static ABSENT: KernelParam =
+            KernelParam::new("test_required_absent", setup_nothing, false).with_required(true);
+        static OPTIONAL: KernelParam =
+            KernelParam::new("test_required_optional", setup_nothing, false);
+
+        let cmdline = dispatch_params("test_required_present=1");
+        assert!(PRESENT.is_required());
+        assert!(!OPTIONAL.is_required());
+        assert_eq!(
+            cmdline.missing_params_in([&PRESENT, &ABSENT, &OPTIONAL]),
+            ["test_required_absent"]
+        );
+
+        let cmdline = dispatch_params("-- test_required_present=1");
+        assert_eq!(
+            cmdline.missing_params_in([&PRESENT, &ABSENT]),
+            ["test_required_present", "test_required_absent"]
+        );
     }
-}
 
-inventory::collect!(KernelParam);
+    #[ktest]
+    fn early_param_with_late_storage_addr_detected() {
+        static TEST_SHARED_SLOT: Once<u32> = Once::new();
+        static TEST_OWN_SLOT: Once<u32> = Once::new();
 
-pub static INIT_PROC_ARGS: Once<InitprocArgs> = Once::new();
+        fn setup_nothing(
+            _occurrences: &[Option<&str>],
+            _notes: &mut ParamNotes,
+        ) -> Result<(), ParamError> {
+            Ok(())
+        }
+        fn shared_slot_addr() -> usize {
+            core::ptr::from_ref(&TEST_SHARED_SLOT).addr()
+        }
+        fn own_slot_addr() -> usize {
+            core::ptr::from_ref(&TEST_OWN_SLOT).addr()
+        }
+        // Submitting the descriptors would warn on every boot of the test kernel.
+        static EARLY: KernelParam = KernelParam::new("test_shared_early", setup_nothing, true)
+            .with_storage_addr(shared_slot_addr);
+        static LATE: KernelParam = KernelParam::new("test_shared_late", setup_nothing, false)
+            .with_storage_addr(shared_slot_addr);
+        static OTHER_EARLY: KernelParam = KernelParam::new("test_own_early", setup_nothing, true)
+            .with_storage_addr(own_slot_addr);
 
-#[init_component]
-fn init() -> Result<(), ComponentInitError> {
-    INIT_PROC_ARGS.call_once(|| dispatch_params(ostd::boot::boot_info().kernel_cmdline.as_str()));
+        assert_eq!(
+            find_same_storage_addr([&EARLY, &LATE, &OTHER_EARLY]),
+            [("test_shared_early", "test_shared_late")]
+        );
+        assert!(find_same_storage_addr([&EARLY, &OTHER_EARLY]).is_empty());
+    }
 
-    Ok(())
-}
+    // The registry is collected with `inventory`, so a test can submit its own
+    // descriptors without the registration macros.
+    #[ktest]
+    fn descriptor_submitted_in_test_dispatched() {
+        use core::sync::atomic::{AtomicU32, Ordering};
 
-// Splits the command line string by spaces but preserve
-// ones that are protected by double quotes(`"`).
-fn split_arg(input: &str) -> impl Iterator<Item = &str> {
-    let mut inside_quotes = false;
+        static TEST_SUBMITTED_SEEN: AtomicU32 = AtomicU32::new(0);
 
-    input.split(move |c: char| {
-        if c == '"' {
-            inside_quotes = !inside_quotes;
+        fn setup_submitted(
+            occurrences: &[Option<&str>],
+            _notes: &mut ParamNotes,
+        ) -> Result<(), ParamError> {
+            let value = occurrences.last().copied().flatten();
+            let value = u32::parse_param(value.ok_or(ParamError::MissingValue)?)?;
+            TEST_SUBMITTED_SEEN.store(value, Ordering::Relaxed);
+            Ok(())
         }
 
-        !inside_quotes && c.is_whitespace()
-    })
-}
+        crate::submit! {
+            KernelParam::new("test_submitted", setup_submitted, false).with_priority(1)
+        }
 
-fn dispatch_params(cmdline: &str) -> InitprocArgs {
-    let mut result: InitprocArgs = InitprocArgs {
-        argv: Vec::new(),
-        envp: Vec::new(),
-    };
+        assert!(
+            matches!(resolve("test_submitted"), ResolveResult::Known(param) if param.priority() == 1)
+        );
+        let cmdline = dispatch_params("test_submitted=42");
+        assert_eq!(TEST_SUBMITTED_SEEN.load(Ordering::Relaxed), 42);
+        assert_eq!(cmdline.stats().matched, 1);
+    }
 
-    let mut kcmdline_end = false;
+    #[ktest]
+    fn params_split_by_phase() {
+        static TEST_PHASE_EARLY: Once<u32> = Once::new();
+        static TEST_PHASE_LATE_A: Once<u32> = Once::new();
+        static TEST_PHASE_LATE_B: Once<bool> = Once::new();
+        crate::define_kv_param_early!("test_phase_early", TEST_PHASE_EARLY);
+        crate::define_kv_param!("test_phase_late_a", TEST_PHASE_LATE_A);
+        crate::define_flag_param!("test_phase_late_b", TEST_PHASE_LATE_B);
 
-    // Step 1: Build lookup from registered param name to handler.
-    let mut registry = BTreeMap::new();
-    for p in inventory::iter::<KernelParam> {
-        if let Some(prev) = registry.insert(p.name, p) {
-            ostd::warn!(
-                "duplicate kernel parameter '{}' registered; keeping last",
-                prev.name
-            );
+        let cmdline = dispatch_params("test_phase_late_b test_phase_early=1 test_phase_late_a=2");
+        let early: Vec<_> = cmdline.early_params().map(KernelParam::name).collect();
+        let late: Vec<_> = cmdline.late_params().map(KernelParam::name).collect();
+        assert_eq!(early, ["test_phase_early"]);
+        assert_eq!(late, ["test_phase_late_a", "test_phase_late_b"]);
+    }
+
+    #[ktest]
+    fn repeatable_param_cleared() {
+        static TEST_CLEARED_CONSOLE: Once<Vec<String>> = Once::new();
+        static TEST_CLEARED_ALL: Once<Vec<String>> = Once::new();
+        crate::define_repeatable_kv_param!("test_cleared_console", TEST_CLEARED_CONSOLE);
+        crate::define_repeatable_kv_param!("test_cleared_all", TEST_CLEARED_ALL);
+
+        let cmdline = dispatch_params(
+            "test_cleared_console=ttyS0 test_cleared_console=- test_cleared_console=tty0 \
+             test_cleared_all=a test_cleared_all=-",
+        );
+        assert!(cmdline.diagnostics().is_empty());
+        assert_eq!(TEST_CLEARED_CONSOLE.get().unwrap(), &["tty0"]);
+        assert!(TEST_CLEARED_ALL.get().unwrap().is_empty());
+    }
+
+    #[ktest]
+    fn unimplemented_params_listed() {
+        let cmdline = dispatch_params("tsc=reliable unknown_arg no-timer-check tsc");
+        let unimplemented: Vec<_> = cmdline.unimplemented_params().collect();
+        assert_eq!(unimplemented, ["no_timer_check", "tsc"]);
+
+        let cmdline = dispatch_params("unknown_arg");
+        assert_eq!(cmdline.unimplemented_params().count(), 0);
+    }
+
+    #[ktest]
+    fn grouped_params_conflict_reported() {
+        static TEST_SCHED_FIFO: Once<bool> = Once::new();
+        static TEST_SCHED_RR: Once<bool> = Once::new();
+        static TEST_SCHED_OTHER: Once<bool> = Once::new();
+        crate::define_flag_param!("test_sched_fifo", TEST_SCHED_FIFO, group = "test_sched");
+        crate::define_flag_param!("test_sched_rr", TEST_SCHED_RR, group = "test_sched");
+        crate::define_flag_param!(
+            "test_sched_other",
+            TEST_SCHED_OTHER,
+            priority = 1,
+            group = "x"
+        );
+
+        let cmdline = dispatch_params("test_sched_rr test_sched_fifo test_sched_other");
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "kernel parameters 'test_sched_fifo' and 'test_sched_rr' conflict in group 'test_sched'"
+            ]
+        );
+        assert_eq!(TEST_SCHED_FIFO.get(), Some(&true));
+        assert_eq!(TEST_SCHED_RR.get(), Some(&true));
+
+        let cmdline = dispatch_params("test_sched_fifo test_sched_other");
+        assert!(cmdline.diagnostics().is_empty());
+    }
+
+    #[ktest]
+    fn quotes_around_token_stripped() {
+        static TEST_QUOTED_TOKEN: Once<String> = Once::new();
+        crate::define_kv_param!("test_quoted_token", TEST_QUOTED_TOKEN);
+
+        let cmdline = dispatch_params("\"test_quoted_token=bar baz\" 'foo=x y' \"arg\"");
+        assert_eq!(TEST_QUOTED_TOKEN.get().unwrap(), "bar baz");
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv(), &[CString::from(c"arg")]);
+        assert_eq!(args.envp(), &[CString::from(c"foo=x y")]);
+    }
+
+    #[ktest]
+    fn empty_values() {
+        static TEST_EMPTY_NUMBER: Once<u32> = Once::new();
+        static TEST_EMPTY_REJECTED: Once<u32> = Once::new();
+        static TEST_EMPTY_STRING: Once<String> = Once::new();
+        crate::define_kv_param!("test_empty_number", TEST_EMPTY_NUMBER);
+        crate::define_kv_param!(
+            "test_empty_rejected",
+            TEST_EMPTY_REJECTED,
+            allow_empty = false
+        );
+        crate::define_kv_param!("test_empty_string", TEST_EMPTY_STRING);
+
+        let cmdline = dispatch_params(
+            "test_empty_number= test_empty_rejected=1 test_empty_rejected= test_empty_string=",
+        );
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "ignoring empty value of kernel parameter 'test_empty_rejected='",
+                "invalid value '' for kernel parameter 'test_empty_number'",
+            ]
+        );
+        assert!(TEST_EMPTY_NUMBER.get().is_none());
+        assert_eq!(TEST_EMPTY_REJECTED.get(), Some(&1));
+        assert_eq!(TEST_EMPTY_STRING.get().unwrap(), "");
+    }
+
+    #[ktest]
+    fn registered_params_listed() {
+        static TEST_LISTED_EARLY: Once<u32> = Once::new();
+        crate::define_kv_param_early!("test_listed_early", TEST_LISTED_EARLY);
+
+        let lines: Vec<_> = registered_params().map(|param| param.to_string()).collect();
+        for expected in [
+            "tsc (late, unimplemented)",
+            "no_timer_check (late, unimplemented)",
+            "test_listed_early (early)",
+        ] {
+            assert!(lines.iter().any(|line| line == expected));
         }
     }
 
-    // Step 2: Tokenize the kernel command line and group recognized param by normalized name.
-    let mut grouped: BTreeMap<String, Vec<Option<&str>>> = BTreeMap::new();
-    for arg in split_arg(cmdline) {
-        // Everything after "--" goes to init.
-        if kcmdline_end {
-            result.argv.push(CString::new(arg).unwrap());
-            continue;
+    #[ktest]
+    fn crlf_and_tabs_split_as_whitespace() {
+        let cmdline = dispatch_params("a\tb=1\tc\r\n");
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv(), &[CString::from(c"a"), CString::from(c"c")]);
+        assert_eq!(args.envp(), &[CString::from(c"b=1")]);
+        assert!(cmdline.diagnostics().is_empty());
+    }
+
+    #[ktest]
+    fn control_char_tokens_ignored() {
+        let cmdline = dispatch_params("key=a\x01b arg \"x\ty\" -- \x1b[0m");
+        let args = cmdline.init_proc_args();
+        assert_eq!(
+            args.argv(),
+            &[CString::from(c"arg"), CString::from(c"x\ty")]
+        );
+        assert!(args.envp().is_empty());
+        assert_eq!(cmdline.diagnostics().len(), 2);
+        assert_eq!(cmdline.stats().ignored, 2);
+
+        assert_eq!(
+            KernelCmdline::try_from_cmdline("key=a\x01b").unwrap_err(),
+            CmdlineError::ControlChar("key=a\x01b")
+        );
+    }
+
+    #[ktest]
+    fn init_env_routing() {
+        let cmdline = dispatch_params("foo=bar baz");
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv(), &[CString::from(c"baz")]);
+        assert_eq!(args.envp(), &[CString::from(c"foo=bar")]);
+
+        let cmdline = dispatch_params("foo=bar baz init_env=off -- qux");
+        let args = cmdline.init_proc_args();
+        assert_eq!(
+            args.argv(),
+            &[
+                CString::from(c"foo=bar"),
+                CString::from(c"baz"),
+                CString::from(c"qux")
+            ]
+        );
+        assert!(args.envp().is_empty());
+        assert!(cmdline.diagnostics().is_empty());
+    }
+
+    #[ktest]
+    fn init_args_as_str() {
+        let cmdline = dispatch_params("HOME=/ -- /bin/sh -c 'echo hi' ünïcode");
+        let args = cmdline.init_proc_args();
+        let argv: Vec<_> = args.argv_str().collect();
+        assert_eq!(argv, ["/bin/sh", "-c", "'echo hi'", "ünïcode"]);
+        let envp: Vec<_> = args.envp_str().collect();
+        assert_eq!(envp, ["HOME=/"]);
+    }
+
+    #[ktest]
+    fn kernel_param_after_separator_warned() {
+        static TEST_LOGLEVEL_AFTER: Once<u32> = Once::new();
+        crate::define_kv_param!("test_loglevel_after", TEST_LOGLEVEL_AFTER);
+
+        let cmdline = dispatch_params("-- test_loglevel_after=3 other=1");
+        assert!(TEST_LOGLEVEL_AFTER.get().is_none());
+        assert_eq!(
+            cmdline.init_proc_args().argv(),
+            &[
+                CString::from(c"test_loglevel_after=3"),
+                CString::from(c"other=1")
+            ]
+        );
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "kernel parameter 'test_loglevel_after=3' after '--' is passed to init, not the kernel"
+            ]
+        );
+    }
+
+    #[ktest]
+    #[should_panic]
+    fn name_with_equal_sign_rejected() {
+        fn setup_nothing(
+            _occurrences: &[Option<&str>],
+            _notes: &mut ParamNotes,
+        ) -> Result<(), ParamError> {
+            Ok(())
         }
-        if arg == "--" {
-            kcmdline_end = true;
-            continue;
+
+        // Registering the parameter with a macro fails to compile instead.
+        let _ = KernelParam::new(
+            core::hint::black_box("test_trailing_equal="),
+            setup_nothing,
+            false,
+        );
+    }
+
+    #[ktest]
+    fn sensitive_values_redacted() {
+        static TEST_SECRET_KEY: Once<String> = Once::new();
+        crate::define_kv_param!("test_secret_key", TEST_SECRET_KEY, sensitive = true);
+
+        let raw = "quiet test-secret-key=abc test_secret_key -- test_secret_key=def";
+        let cmdline = dispatch_params(raw);
+        assert_eq!(
+            cmdline.to_cmdline_string_redacted(),
+            "quiet test-secret-key=**** test_secret_key -- test_secret_key=****"
+        );
+        assert_eq!(cmdline.cmdline(), raw);
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "kernel parameter 'test_secret_key=****' after '--' is passed to init, not the kernel",
+                "kernel parameter 'test_secret_key' requires a value",
+            ]
+        );
+    }
+
+    #[ktest]
+    fn multiline_cmdline_same_as_single_line() {
+        static TEST_MULTILINE_LEVEL: Once<u32> = Once::new();
+        crate::define_kv_param!("test_multiline_level", TEST_MULTILINE_LEVEL);
+
+        let multiline = KernelCmdline::from_multiline(
+            "# Kernel parameters\n\
+             test_multiline_level=3 FOO=bar\n\
+             \n  \
+             # init\n\
+             -- sh\n\
+             -c 'echo #x'\n",
+        );
+        let single_line = dispatch_params("test_multiline_level=3 FOO=bar -- sh -c 'echo #x'");
+
+        assert_eq!(TEST_MULTILINE_LEVEL.get(), Some(&3));
+        assert_eq!(
+            multiline.cmdline(),
+            "test_multiline_level=3 FOO=bar -- sh -c 'echo #x'"
+        );
+        assert_eq!(multiline.init_proc_args(), single_line.init_proc_args());
+        assert_eq!(multiline.stats(), single_line.stats());
+        assert!(multiline.diagnostics().is_empty());
+
+        // The text may be read at runtime, e.g., from a file.
+        let text = String::from("FOO=baz\n-- sh\n");
+        let multiline = KernelCmdline::from_multiline(&text);
+        assert_eq!(multiline.cmdline(), "FOO=baz -- sh");
+    }
+
+    #[ktest]
+    fn initproc_path_from_init_param() {
+        let cmdline = dispatch_params("init=/bin/sh -- arg1");
+        assert_eq!(cmdline.initproc_path(), Some("/bin/sh"));
+        let argv: Vec<_> = cmdline.init_proc_args().argv_str().collect();
+        assert_eq!(argv, ["arg1"]);
+
+        let cmdline = dispatch_params("init=/bin/sh init=/sbin/init init=");
+        assert_eq!(cmdline.initproc_path(), Some("/sbin/init"));
+
+        // The kernel passes the path as `argv[0]`, so it is not in the arguments.
+        let cmdline = dispatch_params("init=/bin/sh");
+        assert_eq!(cmdline.initproc_path(), Some("/bin/sh"));
+        assert!(cmdline.init_proc_args().argv().is_empty());
+    }
+
+    #[ktest]
+    fn initproc_path_after_other_params() {
+        let cmdline = dispatch_params("single TERM=linux init=/bin/sh -- foo");
+        assert_eq!(cmdline.initproc_path(), Some("/bin/sh"));
+        let args = cmdline.init_proc_args();
+        assert_eq!(args.argv_str().collect::<Vec<_>>(), ["single", "foo"]);
+        assert_eq!(args.envp_str().collect::<Vec<_>>(), ["TERM=linux"]);
+
+        // After "--", `init=` is an init argument.
+        let cmdline = dispatch_params("init=/bin/sh -- init=/bin/bash");
+        assert_eq!(cmdline.initproc_path(), Some("/bin/sh"));
+        assert_eq!(
+            cmdline.init_proc_args().argv_str().collect::<Vec<_>>(),
+            ["init=/bin/bash"]
+        );
+    }
+
+    #[ktest]
+    fn initproc_path_kept_by_each_cmdline() {
+        let sh = dispatch_params("init=/bin/sh");
+        let init = dispatch_params("init=/sbin/init");
+        let none = dispatch_params("quiet");
+        assert_eq!(sh.initproc_path(), Some("/bin/sh"));
+        assert_eq!(init.initproc_path(), Some("/sbin/init"));
+        assert_eq!(none.initproc_path(), None);
+        assert_eq!(
+            sh.effective_params().collect::<Vec<_>>(),
+            [("init", Some("/bin/sh"))]
+        );
+    }
+
+    #[ktest]
+    fn initproc_path_set_up_as_param() {
+        let cmdline = dispatch_params("init=/bin/sh cmdline_disable=init");
+        assert_eq!(cmdline.initproc_path(), None);
+        assert!(cmdline.init_proc_args().envp().is_empty());
+
+        let cmdline = dispatch_params("init=/bin/sh init");
+        assert_eq!(cmdline.initproc_path(), None);
+        assert_eq!(
+            cmdline.diagnostics(),
+            ["kernel parameter 'init' requires a value"]
+        );
+
+        let mut cmdline = dispatch_params("init=/bin/sh");
+        cmdline.extend_from("quiet");
+        assert_eq!(cmdline.initproc_path(), Some("/bin/sh"));
+        cmdline.extend_from("init=/sbin/init");
+        assert_eq!(cmdline.initproc_path(), Some("/sbin/init"));
+    }
+
+    #[ktest]
+    fn initproc_path_absent() {
+        let cmdline = dispatch_params("-- /sbin/custom arg");
+        assert_eq!(cmdline.initproc_path(), None);
+        let argv: Vec<_> = cmdline.init_proc_args().argv_str().collect();
+        assert_eq!(argv, ["/sbin/custom", "arg"]);
+    }
+
+    #[ktest]
+    fn disabled_params_not_set_up() {
+        static TEST_DISABLED_LEVEL: Once<u32> = Once::new();
+        static TEST_DISABLED_EARLY: Once<u32> = Once::new();
+        static TEST_NOT_DISABLED: Once<u32> = Once::new();
+        crate::define_kv_param!("test_disabled_level", TEST_DISABLED_LEVEL);
+        crate::define_kv_param_early!("test_disabled_early", TEST_DISABLED_EARLY);
+        crate::define_kv_param!("test_not_disabled", TEST_NOT_DISABLED);
+
+        let cmdline = dispatch_params(
+            "cmdline_disable=test_disabled_level test_disabled_level=3 \
+             test_disabled_early=1 test_not_disabled=2 cmdline-disable=test-disabled-early",
+        );
+        assert!(TEST_DISABLED_LEVEL.get().is_none());
+        assert!(TEST_DISABLED_EARLY.get().is_none());
+        assert_eq!(TEST_NOT_DISABLED.get(), Some(&2));
+        assert!(cmdline.diagnostics().is_empty());
+        assert!(cmdline.init_proc_args().envp().is_empty());
+    }
+
+    #[ktest]
+    fn split_tokens_same_as_line() {
+        static TEST_SPLIT_MSG: Mutex<Option<String>> = Mutex::new(None);
+        crate::define_kv_param!("test_split_msg", TEST_SPLIT_MSG);
+
+        let split = KernelCmdline::from(
+            &[
+                "test_split_msg=hello world",
+                "",
+                "FOO=bar",
+                "=x",
+                "--",
+                "sh",
+                "-c",
+                "true",
+            ][..],
+        );
+        assert_eq!(TEST_SPLIT_MSG.lock().as_deref(), Some("hello world"));
+        let line = dispatch_params("'test_split_msg=hello world' FOO=bar =x -- sh -c true");
+        assert_eq!(TEST_SPLIT_MSG.lock().as_deref(), Some("hello world"));
+
+        assert_eq!(split.init_proc_args(), line.init_proc_args());
+        assert_eq!(split.stats(), line.stats());
+        assert_eq!(
+            split.diagnostics(),
+            ["ignoring malformed kernel parameter '=x' at byte 36"]
+        );
+    }
+
+    #[ktest]
+    fn help_read_back() {
+        static TEST_HELPFUL: Once<u32> = Once::new();
+        static TEST_HELPLESS: Once<u32> = Once::new();
+        crate::define_kv_param!("test_helpless", TEST_HELPLESS);
+        crate::define_kv_param!(
+            "test_helpful",
+            TEST_HELPFUL,
+            priority = 1,
+            help = "Sets a value for testing"
+        );
+
+        let param = registered_params()
+            .find(|param| param.name() == "test_helpful")
+            .unwrap();
+        assert_eq!(param.help(), "Sets a value for testing");
+        assert_eq!(param.priority(), 1);
+
+        let param = registered_params()
+            .find(|param| param.name() == "test_helpless")
+            .unwrap();
+        assert_eq!(param.help(), "");
+
+        let param = registered_params()
+            .find(|param| param.name() == "init")
+            .unwrap();
+        assert_eq!(param.help(), "Sets the path of the init executable");
+    }
+
+    #[ktest]
+    fn invalid_values_named_in_diagnostics() {
+        static TEST_BAD_LOGLEVEL: Once<u32> = Once::new();
+        static TEST_BAD_SECRET: Once<u32> = Once::new();
+        static TEST_BAD_LIST: Once<Vec<u32>> = Once::new();
+        crate::define_kv_param!("test_bad_loglevel", TEST_BAD_LOGLEVEL);
+        crate::define_kv_param!("test_bad_secret", TEST_BAD_SECRET, sensitive = true);
+        crate::define_repeatable_kv_param!("test_bad_list", TEST_BAD_LIST);
+
+        let cmdline = dispatch_params(
+            "test_bad_loglevel=abc test_bad_secret=xyz test_bad_list=x test_bad_list=1",
+        );
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "invalid value for kernel parameter 'test_bad_list'",
+                "invalid value 'abc' for kernel parameter 'test_bad_loglevel'",
+                "invalid value '****' for kernel parameter 'test_bad_secret'",
+            ]
+        );
+    }
+
+    #[ktest]
+    fn duplicate_names_resolved_deterministically() {
+        fn setup_nothing(
+            _occurrences: &[Option<&str>],
+            _notes: &mut ParamNotes,
+        ) -> Result<(), ParamError> {
+            Ok(())
         }
 
-        let (key, value) = match arg.find('=') {
-            Some(pos) => (&arg[..pos], Some(&arg[pos + 1..])),
-            None => (arg, None),
-        };
-        // Normalize hyphens to underscores (Linux compatibility)
-        let normalized = key.replace('-', "_");
+        static FIRST: KernelParam =
+            KernelParam::new("test_duplicate", setup_nothing, false).at_location("a.rs:1");
+        static SECOND: KernelParam =
+            KernelParam::new("test_duplicate", setup_nothing, true).at_location("b.rs:1");
 
-        if registry.contains_key(normalized.as_str()) {
-            // Group by normalized name
-            grouped.entry(normalized).or_default().push(value);
-        } else {
-            // Unknown parameter: forward to init
-            if key.contains('.') {
-                // The entry contains a dot, which is treated as a module argument.
-                // Unrecognized module arguments are ignored.
-                continue;
-            } else if let Some(value) = value {
-                // If the entry is not recognized, it is passed to the init process.
-                // Pattern 'entry=value' is treated as the init environment.
-                let envp_entry = CString::new(key.to_string() + "=" + value).unwrap();
-                result.envp.push(envp_entry);
-            } else {
-                // If the entry is not recognized, it is passed to the init process.
-                // Pattern 'entry' without value is treated as the init argument.
-                let argv_entry = CString::new(key.to_string()).unwrap();
-                result.argv.push(argv_entry);
-            }
+        for params in [vec![&FIRST, &SECOND], vec![&SECOND, &FIRST]] {
+            let registry = Registry::from_params(params);
+            let param = registry.get("test_duplicate").unwrap();
+            assert!(core::ptr::eq(param, &SECOND));
         }
     }
 
-    // Step 3: Dispatch each group to its handler.
-    let (early_params, params): (Vec<_>, Vec<_>) = grouped
-        .iter()
-        .filter_map(|(name, occurrences)| registry.get(name.as_str()).map(|p| (*p, occurrences)))
-        .partition(|(p, _)| p.early);
+    #[ktest]
+    fn independent_cmdlines_parsed_in_turn() {
+        static TEST_TABLE_LEVEL: Mutex<Option<u32>> = Mutex::new(None);
+        crate::define_kv_param!("test_table_level", TEST_TABLE_LEVEL);
 
-    early_params
-        .into_iter()
-        .chain(params)
-        .for_each(|(param, occurrences)| (param.setup_fn)(occurrences));
+        for (line, level, argv_len) in [
+            ("test_table_level=1", Some(1), 0),
+            ("test_table_level=2 single", Some(2), 1),
+            ("-- test_table_level=3", Some(2), 1),
+        ] {
+            let cmdline = KernelCmdline::try_from_cmdline(line).unwrap();
+            assert_eq!(*TEST_TABLE_LEVEL.lock(), level);
+            assert_eq!(cmdline.init_proc_args().argv().len(), argv_len);
+        }
+    }
 
-    result
-}
+    #[ktest]
+    fn global_cmdline_accessors() {
+        // The global of the kernel under test is left to `init`.
+        static TEST_CMDLINE: Once<KernelCmdline> = Once::new();
+        assert!(try_get_parsed(&TEST_CMDLINE).is_none());
 
-#[cfg(ktest)]
-mod tests {
-    use ostd::prelude::*;
+        TEST_CMDLINE.call_once(|| dispatch_params("quiet"));
+        let cmdline = try_get_parsed(&TEST_CMDLINE).unwrap();
+        assert_eq!(cmdline.cmdline(), "quiet");
+        assert!(core::ptr::eq(cmdline, get_parsed(&TEST_CMDLINE)));
+        assert!(core::ptr::eq(cmdline, TEST_CMDLINE.get().unwrap()));
+    }
 
-    use super::*;
+    #[ktest]
+    #[should_panic]
+    fn global_cmdline_get_before_parsed() {
+        static TEST_CMDLINE: Once<KernelCmdline> = Once::new();
+        get_parsed(&TEST_CMDLINE);
+    }
 
     #[ktest]
-    fn unknown_kv_forwarded_to_init_env() {
-        let args = dispatch_params("unknown_key=1");
-        assert!(args.envp().iter().any(|e| e.to_bytes() == b"unknown_key=1"));
+    fn proc_cmdline_verbatim() {
+        let cmdline = dispatch_params("quiet  FOO=bar tsc=reliable -- sh -c 'echo hi'");
+        assert_eq!(
+            cmdline.proc_cmdline_bytes(),
+            b"quiet  FOO=bar tsc=reliable -- sh -c 'echo hi'\n"
+        );
+
+        assert_eq!(dispatch_params("").proc_cmdline_bytes(), b"\n");
     }
 
     #[ktest]
-    fn dotted_unknown_param_not_forwarded() {
-        let args = dispatch_params("some_module.flag");
+    fn hyphens_match_underscores() {
+        static TEST_LOG_LEVEL: Once<String> = Once::new();
+        crate::define_kv_param!("test_log_level", TEST_LOG_LEVEL);
+
+        let cmdline = dispatch_params("test-log_level=info-x");
+        assert_eq!(TEST_LOG_LEVEL.get().unwrap(), "info-x");
+        assert!(cmdline.init_proc_args().envp().is_empty());
+
+        let param = registered_params()
+            .find(|param| param.name() == "test_log_level")
+            .unwrap();
+        assert!(param.matches_name("test-log-level"));
+        assert!(param.matches_name("test_log_level"));
+        assert!(!param.matches_name("test_log_level="));
+        assert!(!param.matches_name("test.log_level"));
+    }
+
+    #[ktest]
+    fn effective_params_only_include_params_in_effect() {
+        static TEST_EFFECTIVE_KV: Once<u32> = Once::new();
+        static TEST_EFFECTIVE_FLAG: Once<bool> = Once::new();
+        static TEST_EFFECTIVE_INVALID: Once<u32> = Once::new();
+        static TEST_EFFECTIVE_SECRET: Once<String> = Once::new();
+        crate::define_kv_param!("test_effective_kv", TEST_EFFECTIVE_KV);
+        crate::define_flag_param!("test_effective_flag", TEST_EFFECTIVE_FLAG);
+        crate::define_kv_param!("test_effective_invalid", TEST_EFFECTIVE_INVALID);
+        crate::define_kv_param!(
+            "test_effective_secret",
+            TEST_EFFECTIVE_SECRET,
+            sensitive = true
+        );
+        crate::define_unimplemented_param!("test_effective_unimpl");
+
+        let cmdline = dispatch_params(
+            "test_effective_unimpl=1 test_effective_kv=1 test_effective_flag \
+             test_effective_invalid=x test_effective_secret=hunter2 test_effective_kv=2",
+        );
+        assert!(cmdline.has_unimplemented());
+        assert_eq!(
+            cmdline.effective_params().collect::<Vec<_>>(),
+            vec![
+                ("test_effective_flag", None),
+                ("test_effective_kv", Some("2")),
+                ("test_effective_secret", Some("****")),
+            ]
+        );
+    }
+
+    #[ktest]
+    fn truncated_tokens_with_empty_names_are_dropped() {
+        static TEST_TRUNCATED: Once<u32> = Once::new();
+        crate::define_kv_param!("test_truncated", TEST_TRUNCATED);
+
+        // A command line cut off by the bootloader may end in a dangling `=`.
+        let cmdline = dispatch_params("unknown_key=1 = =value test_truncated=");
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "ignoring malformed kernel parameter '=' at byte 14",
+                "ignoring malformed kernel parameter '=value' at byte 16",
+                "invalid value '' for kernel parameter 'test_truncated'",
+            ]
+        );
+        assert!(TEST_TRUNCATED.get().is_none());
+
+        let args = cmdline.init_proc_args();
         assert!(args.argv().is_empty());
-        assert!(args.envp().is_empty());
+        assert_eq!(args.envp(), &[CString::from(c"unknown_key=1")]);
+        assert_eq!(cmdline.stats().ignored, 2);
+
+        assert_eq!(
+            KernelCmdline::try_from_cmdline("unknown_key=1 =").unwrap_err(),
+            CmdlineError::MalformedToken("=")
+        );
     }
 
     #[ktest]
-    fn repeated_unknown_kv_and_arg_forwarding() {
-        let args = dispatch_params("unknown_key=1 unknown_key unknown_key=2");
+    fn log_level_controls_diagnostics() {
+        static TEST_LOG_LEVEL_KV: Once<u32> = Once::new();
+        crate::define_kv_param!("test_log_level_kv", TEST_LOG_LEVEL_KV);
 
-        assert_eq!(args.envp.len(), 2);
-        assert_eq!(args.envp[0].to_bytes(), b"unknown_key=1");
-        assert_eq!(args.envp[1].to_bytes(), b"unknown_key=2");
+        let cmdline = dispatch_params("=x test_log_level_kv=abc cmdline_loglevel=0 unknown");
+        assert!(cmdline.diagnostics().is_empty());
+        assert_eq!(cmdline.stats().ignored, 1);
 
-        assert_eq!(args.argv.len(), 1);
-        assert_eq!(args.argv[0].to_bytes(), b"unknown_key");
+        let cmdline = dispatch_params("=x cmdline_loglevel=1");
+        assert_eq!(
+            cmdline.diagnostics(),
+            ["ignoring malformed kernel parameter '=x' at byte 0"]
+        );
+
+        let cmdline =
+            dispatch_params("cmdline-loglevel=3 test_log_level_kv=1 unknown key=value -- arg");
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "dispatching 'test_log_level_kv' to kernel parameter 'test_log_level_kv'",
+                "forwarding 'unknown' to init as an argument",
+                "forwarding 'key=value' to init as an environment variable",
+                "forwarding 'arg' to init as an argument",
+            ]
+        );
+
+        let cmdline = dispatch_params("cmdline_loglevel=4 -- cmdline_loglevel=0 =x");
+        assert_eq!(
+            cmdline.diagnostics(),
+            ["invalid value for kernel parameter 'cmdline_loglevel'"]
+        );
     }
 
     #[ktest]
-    fn registered_params_not_forwarded() {
-        static TEST_VARIABLE: Once<u32> = Once::new();
-        static TEST_VARIABLE_REPEATED: Once<Vec<String>> = Once::new();
+    fn debug_dump_lists_params_and_init_args() {
+        static TEST_DUMP_KV: Once<u32> = Once::new();
+        static TEST_DUMP_LIST: Once<Vec<String>> = Once::new();
+        crate::define_kv_param!("test_dump_kv", TEST_DUMP_KV);
+        crate::define_repeatable_kv_param!("test_dump_list", TEST_DUMP_LIST);
+        crate::define_unimplemented_param!("test_dump_unimpl");
 
-        crate::define_kv_param!("log_level", TEST_VARIABLE);
-        crate::define_repeatable_kv_param!("console", TEST_VARIABLE_REPEATED);
+        let cmdline = dispatch_params(
+            "test_dump_list=a test_dump_kv=1 test_dump_unimpl key=value test_dump_list=b -- arg",
+        );
+        let dump = cmdline.debug_dump();
+        assert!(dump.contains("test_dump_kv (late): test_dump_kv=1\n"));
+        assert!(dump.contains("test_dump_list (late): test_dump_list=a test_dump_list=b\n"));
+        assert!(dump.contains("test_dump_unimpl (late, unimplemented): not in effect\n"));
+        assert!(dump.ends_with("argv[0]: arg\nenvp[0]: key=value\n"));
+    }
 
-        let args = dispatch_params("log_level=4 console=ttyS0 console=ttyS1");
+    #[ktest]
+    fn dropped_module_params_recorded() {
+        let cmdline = dispatch_params("unknown.driver.x=1 unknown_arg test.flag -- later.x=2");
+        assert_eq!(
+            cmdline.dropped_module_params().collect::<Vec<_>>(),
+            vec![("unknown.driver.x", Some("1")), ("test.flag", None)]
+        );
+        assert_eq!(cmdline.stats().ignored, 2);
+        assert_eq!(cmdline.init_proc_args().argv().len(), 2);
+    }
+
+    #[ktest]
+    fn split_tokens_with_empty_names_are_dropped() {
+        static TOKENS: &[&str] = &["=", "=foo", "a=b", "= x"];
+        let cmdline = KernelCmdline::from(TOKENS);
+        assert_eq!(
+            cmdline.diagnostics(),
+            [
+                "ignoring malformed kernel parameter '=' at byte 0",
+                "ignoring malformed kernel parameter '=foo' at byte 2",
+                "ignoring malformed kernel parameter '= x' at byte 11",
+            ]
+        );
+        let args = cmdline.init_proc_args();
         assert!(args.argv().is_empty());
-        assert!(args.envp().is_empty());
+        assert_eq!(args.envp(), &[CString::from(c"a=b")]);
+    }
+
+    crate::define_kv_param_accessor! {
+        fn test_accessor_level() -> Option<u32> = "test_accessor_level";
+    }
+    crate::define_kv_param_accessor! {
+        fn test_accessor_root() -> Option<&'static String> = "test_accessor_root",
+            allow_empty = false;
+    }
+    crate::define_kv_param_accessor! {
+        fn test_accessor_all_options() -> Option<u32> = "test_accessor_all_options",
+            priority = 1,
+            group = "test_accessor_group",
+            allow_empty = false,
+            sensitive = true,
+            required = false,
+            help = "Sets a value for testing";
+    }
+
+    #[ktest]
+    fn accessors_read_parsed_values() {
+        dispatch_params("test_accessor_level=3 test_accessor_root= test_accessor_root=/dev/vda");
+        assert_eq!(test_accessor_level(), Some(3));
+        assert_eq!(test_accessor_root().map(String::as_str), Some("/dev/vda"));
+    }
+
+    #[ktest]
+    fn accessor_takes_all_options() {
+        dispatch_params("test_accessor_all_options=5");
+        assert_eq!(test_accessor_all_options(), Some(5));
+
+        let param = registered_params()
+            .find(|param| param.name() == "test_accessor_all_options")
+            .unwrap();
+        assert_eq!(param.priority(), 1);
+        assert_eq!(param.group(), Some("test_accessor_group"));
+        assert!(param.is_sensitive());
+        assert!(!param.is_required());
+        assert_eq!(param.help(), "Sets a value for testing");
+    }
+
+    #[ktest]
+    fn trace_hook_observes_decisions_in_order() {
+        static TEST_TRACE_KV: Once<u32> = Once::new();
+        static TEST_TRACE_EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        crate::define_kv_param!("test_trace_kv", TEST_TRACE_KV);
+
+        // Other tests may parse their command lines concurrently.
+        fn record(event: &CmdlineEvent<'_>) {
+            let line = match event {
+                CmdlineEvent::Param { token, param } => format!("param {} {}", token, param),
+                CmdlineEvent::Builtin(token) => format!("builtin {}", token),
+                CmdlineEvent::InitArg(token) => format!("arg {}", token),
+                CmdlineEvent::InitEnv(token) => format!("env {}", token),
+                CmdlineEvent::Dropped(token) => format!("dropped {}", token),
+            };
+            if line.contains("test_trace") {
+                TEST_TRACE_EVENTS.lock().push(line);
+            }
+        }
+
+        set_cmdline_trace(Some(record));
+        dispatch_params(
+            "test_trace_arg test_trace_kv=1 =test_trace test_trace_env=1 \
+             test_trace.mod dump_init=test_trace -- test_trace_after",
+        );
+        set_cmdline_trace(None);
+        dispatch_params("test_trace_unobserved");
+
+        assert_eq!(
+            *TEST_TRACE_EVENTS.lock(),
+            [
+                "arg test_trace_arg",
+                "param test_trace_kv=1 test_trace_kv (late)",
+                "dropped =test_trace",
+                "env test_trace_env=1",
+                "dropped test_trace.mod",
+                "builtin dump_init=test_trace",
+                "arg test_trace_after",
+            ]
+        );
+    }
+
+    #[ktest]
+    fn cmdline_kept_verbatim() {
+        let raw = "  quiet\tfoo=\"a  b\"   -- arg  ";
+        assert_eq!(dispatch_params(raw).cmdline(), raw);
+        assert_eq!(KernelCmdline::try_from_cmdline(raw).unwrap().cmdline(), raw);
+    }
+
+    #[ktest]
+    fn indexed_param_records_token_positions() {
+        static TEST_INDEXED: Mutex<Vec<(usize, String)>> = Mutex::new(Vec::new());
+        crate::define_indexed_kv_param!("test_indexed", TEST_INDEXED);
+
+        dispatch_params("test_indexed=a quiet other=1 test-indexed=b -- test_indexed=c");
+        assert_eq!(
+            *TEST_INDEXED.lock(),
+            [(0, String::from("a")), (3, String::from("b"))]
+        );
+
+        dispatch_params("test_indexed=a test_indexed=- arg test_indexed=b");
+        assert_eq!(*TEST_INDEXED.lock(), [(3, String::from("b"))]);
     }
 }
@@ -10,10 +10,20 @@
 //!   `define_flag_param!` are used by components to register handlers/storage
 //!   for parameters. These macros submit `KernelParam` entries into the
 //!   `inventory` registry which the `dispatch` module consumes at boot.
+//! - `define_prefix_param!` registers a handler that receives every parameter
+//!   under a name prefix (e.g. `mydrv.`), one occurrence at a time.
+//!   `define_kernel_param_module!` does the same for the sub-keys of a module
+//!   (e.g. `e1000.debug` of `e1000`).
+//! - `register_cmdline_validator!` registers a check that runs after all the
+//!   parameters are set up, e.g. to check the constraints among parameters.
 //!
 //! Key traits
 //! - `ParseParamValue`, `ParseRepeatableParamValue`, `ParseFlag`: parsing
 //!   traits that convert raw `&str` tokens into typed values.
+//!   The registration macros require the matching trait of the storage value,
+//!   so e.g. a flag type cannot be registered for a `key=value` parameter.
+//!   Parameter names are checked when registered: a name with `-` or `=`
+//!   fails to compile.
 //! - `ParamStorage`: storage abstraction used by components to receive parsed
 //!   values (examples: `spin::Once<T>`, atomic types, `Once<Vec<T>>`).
 //!
@@ -25,6 +35,31 @@
 //!   occurrences and calls the corresponding setup functions. Unrecognized
 //!   tokens are forwarded to the init process as `argv` (bare tokens) or
 //!   `envp` (`key=value`).
+//! - A built-in command line (see `ostd::boot::BootInfo::builtin_kernel_cmdline`)
+//!   is parsed after the one from the bootloader. Its parameters only take
+//!   effect if the bootloader command line does not specify them.
+//! - The special flag `cmdline_strict` switches the remaining tokens of the
+//!   command line to strict mode, in which unrecognized tokens are reported as
+//!   errors instead of being forwarded to the init process.
+//! - The special flag `strict_init` reports all the unrecognized tokens before
+//!   `--` as errors, wherever the flag appears before `--`.
+//! - The special flag `init_env=off` makes the unrecognized `key=value` tokens
+//!   forwarded to the init process as `argv` instead of `envp`.
+//! - The special flag `dump_init` logs the arguments passed to the init process.
+//! - The parameter `init=PATH` sets the init executable, whose path is passed
+//!   as `argv[0]`. See [`KernelCmdline::initproc_path`].
+//! - The special parameter `cmdline_disable=NAME[,NAME...]` skips the setup
+//!   of the listed parameters, wherever it appears before `--`.
+//! - The special parameter `max_init_args=N` limits the number of tokens after
+//!   `--` that are forwarded to the init process as `argv`.
+//! - The special flag `init_expand_env` (off by default) substitutes the
+//!   references `$VAR` and `${VAR}` in the init arguments and environment
+//!   values with the value of `VAR` in the init `envp`. An environment value
+//!   only refers to the variables defined before it. The references to
+//!   undefined variables are kept and reported.
+//! - The special parameter `cmdline_loglevel=N` (0 to 3, 2 by default) sets
+//!   how verbose the parser's own diagnostics are, wherever it appears before
+//!   `--`. See [`KernelCmdline::diagnostics`].
 //!
 //! Relationship to components
 //! - This crate integrates with the component initialization system. The cmdline
@@ -62,24 +97,52 @@ pub mod parse;
 pub mod types;
 mod unimplemented;
 
-pub use dispatch::INIT_PROC_ARGS;
 #[doc(hidden)]
-pub use dispatch::KernelParam;
+pub use alloc::string::String as __String;
+
+pub use dispatch::{
+    CmdlineError, CmdlineEvent, CmdlineStats, CmdlineValidator, InitprocArgs, KERNEL_CMDLINE,
+    KernelCmdline, KernelParam, ResolveResult, dump_params, registered_params, resolve,
+    set_cmdline_trace,
+};
 #[doc(hidden)]
 pub use inventory::submit;
 #[doc(hidden)]
-pub use unimplemented::setup_unimplemented;
+pub use spin::Once as __Once;
 
 /// Defines a **single-value** `key=value` kernel command-line parameter.
 ///
 /// During command-line parsing, all occurrences of `$name` are grouped and then
 /// handled with **last-wins** semantics.
 ///
+/// Note that the last-wins semantics applies within a command line. A
+/// `spin::Once<T>` storage ignores the values stored after the first one, so
+/// it keeps the value of the first command line ever parsed. Use a
+/// `spin::Mutex<Option<T>>` storage if the value should be overwritten by each
+/// parsed command line.
+///
 /// # Arguments
 ///
 /// - `$name`: Parameter name (e.g. `"log_level"`).
 /// - `$storage`: Storage location for the parsed value. Its type must implement
 ///   [`crate::parse::ParamStorage`].
+/// - `priority = $priority` (optional): A `u8` that decides the setup order
+///   among the parameters of the same phase; higher ones are set up first.
+///   See [`KernelParam::priority`].
+/// - `group = $group` (optional): A `&'static str` naming a group of mutually
+///   exclusive parameters. If more than one parameter of the group is on the
+///   command line, a diagnostic is reported, but all of them still take effect.
+/// - `allow_empty = $allow_empty` (optional): A `bool`, `true` by default. An
+///   empty value (e.g. `foo=`) is passed to the parser if it is `true`, and is
+///   rejected with a diagnostic otherwise.
+/// - `sensitive = $sensitive` (optional): A `bool`, `false` by default. The
+///   value of a sensitive parameter (e.g. a key) is masked in
+///   [`KernelCmdline::to_cmdline_string_redacted`] and in the diagnostics.
+/// - `required = $required` (optional): A `bool`, `false` by default. The boot
+///   fails if a required parameter is not on the command line. See
+///   [`KernelCmdline::missing_required_params`].
+/// - `help = $help` (optional): A `&'static str` describing the parameter in a
+///   short sentence, empty by default. See [`KernelParam::help`].
 ///
 /// # Parsing
 ///
@@ -95,8 +158,18 @@ pub use unimplemented::setup_unimplemented;
 /// ```
 #[macro_export]
 macro_rules! define_kv_param {
-    ($name:expr, $storage:expr) => {
-        $crate::__define_param!(@late, $name, $storage, $crate::parse::setup_kv_param);
+    (
+        $name:expr, $storage:expr
+        $(, priority = $priority:expr)? $(, group = $group:expr)?
+        $(, allow_empty = $allow_empty:expr)? $(, sensitive = $sensitive:expr)?
+        $(, required = $required:expr)? $(, help = $help:expr)? $(,)?
+    ) => {
+        $crate::__define_param!(
+            @late, $name, $storage, $crate::parse::setup_kv_param
+            $(, .with_priority($priority))? $(, .in_group($group))?
+            $(, .with_allow_empty($allow_empty))? $(, .with_sensitive($sensitive))?
+            $(, .with_required($required))? $(, .with_help($help))?
+        );
     };
 }
 
@@ -105,8 +178,93 @@ macro_rules! define_kv_param {
 /// Almost same as [`define_kv_param!`], but it is processed earlier in the boot sequence.
 #[macro_export]
 macro_rules! define_kv_param_early {
-    ($name:expr, $storage:expr) => {
-        $crate::__define_param!(@early, $name, $storage, $crate::parse::setup_kv_param);
+    (
+        $name:expr, $storage:expr
+        $(, priority = $priority:expr)? $(, group = $group:expr)?
+        $(, allow_empty = $allow_empty:expr)? $(, sensitive = $sensitive:expr)?
+        $(, required = $required:expr)? $(, help = $help:expr)? $(,)?
+    ) => {
+        $crate::__define_param!(
+            @early, $name, $storage, $crate::parse::setup_kv_param
+            $(, .with_priority($priority))? $(, .in_group($group))?
+            $(, .with_allow_empty($allow_empty))? $(, .with_sensitive($sensitive))?
+            $(, .with_required($required))? $(, .with_help($help))?
+        );
+    };
+}
+
+/// Defines a `key=value` kernel command-line parameter read through a function.
+///
+/// Almost same as [`define_kv_param!`], but the storage is a `spin::Once`
+/// hidden in the defined function, which returns the parsed value or `None`
+/// if the parameter is not given. The function returns a copy of the value if
+/// its return type is `Option<T>`, which requires `T: Copy`, and a reference
+/// to the value if its return type is `Option<&'static T>`.
+///
+/// The optional arguments after the name are the same as in [`define_kv_param!`],
+/// and must be given in the same order: `priority`, `group`, `allow_empty`,
+/// `sensitive`, `required`, and then `help`.
+///
+/// # Examples
+///
+/// ```ignore
+/// define_kv_param_accessor! {
+///     /// Returns the log level given by the `log_level` parameter.
+///     pub fn log_level() -> Option<u32> = "log_level";
+/// }
+/// define_kv_param_accessor! {
+///     pub fn root() -> Option<&'static String> = "root", allow_empty = false;
+/// }
+/// ```
+#[macro_export]
+macro_rules! define_kv_param_accessor {
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $fn_name:ident() -> Option<&'static $ty:ty> = $name:expr
+        $(, $option:ident = $value:expr)* $(,)?;
+    ) => {
+        $(#[$attr])*
+        $vis fn $fn_name() -> Option<&'static $ty> {
+            static STORAGE: $crate::__Once<$ty> = $crate::__Once::new();
+            $crate::define_kv_param!($name, STORAGE $(, $option = $value)*);
+            STORAGE.get()
+        }
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $fn_name:ident() -> Option<$ty:ty> = $name:expr
+        $(, $option:ident = $value:expr)* $(,)?;
+    ) => {
+        $(#[$attr])*
+        $vis fn $fn_name() -> Option<$ty> {
+            static STORAGE: $crate::__Once<$ty> = $crate::__Once::new();
+            $crate::define_kv_param!($name, STORAGE $(, $option = $value)*);
+            STORAGE.get().copied()
+        }
+    };
+}
+
+/// Defines a **single-value** `key:value` kernel command-line parameter.
+///
+/// Almost same as [`define_kv_param!`], but the name and the value are
+/// separated by the first `:` instead of `=`. For example, with
+/// `define_colon_kv_param!("trace", TRACE)`, the token `trace:level=debug` gives
+/// `level=debug` as the value.
+#[macro_export]
+macro_rules! define_colon_kv_param {
+    (
+        $name:expr, $storage:expr
+        $(, priority = $priority:expr)? $(, group = $group:expr)?
+        $(, allow_empty = $allow_empty:expr)? $(, sensitive = $sensitive:expr)?
+        $(, required = $required:expr)? $(, help = $help:expr)? $(,)?
+    ) => {
+        $crate::__define_param!(
+            @late, $name, $storage, $crate::parse::setup_kv_param,
+            .colon_separated()
+            $(, .with_priority($priority))? $(, .in_group($group))?
+            $(, .with_allow_empty($allow_empty))? $(, .with_sensitive($sensitive))?
+            $(, .with_required($required))? $(, .with_help($help))?
+        );
     };
 }
 
@@ -115,11 +273,21 @@ macro_rules! define_kv_param_early {
 /// Unlike [`define_kv_param!`], this parameter may appear multiple times; the
 /// framework collects **all** values and passes them to the parser in one shot.
 ///
+/// A value of `-` (see [`crate::parse::CLEAR_VALUE`]) discards the values
+/// before it, e.g. `console=ttyS0 console=- console=tty0` gives only `tty0`.
+/// So `-` itself cannot be a value of a repeatable parameter.
+///
 /// # Arguments
 ///
 /// - `$name`: Parameter name (e.g. `"console"`).
 /// - `$storage`: Storage location for the parsed value. Its type must implement
 ///   [`crate::parse::ParamStorage`].
+/// - `priority = $priority` (optional): Same as in [`define_kv_param!`].
+/// - `group = $group` (optional): Same as in [`define_kv_param!`].
+/// - `allow_empty = $allow_empty` (optional): Same as in [`define_kv_param!`].
+/// - `sensitive = $sensitive` (optional): Same as in [`define_kv_param!`].
+/// - `required = $required` (optional): Same as in [`define_kv_param!`].
+/// - `help = $help` (optional): Same as in [`define_kv_param!`].
 ///
 /// # Parsing
 ///
@@ -135,8 +303,54 @@ macro_rules! define_kv_param_early {
 /// ```
 #[macro_export]
 macro_rules! define_repeatable_kv_param {
-    ($name:expr, $storage:expr) => {
-        $crate::__define_param!(@late, $name, $storage, $crate::parse::setup_repeatable_kv_param);
+    (
+        $name:expr, $storage:expr
+        $(, priority = $priority:expr)? $(, group = $group:expr)?
+        $(, allow_empty = $allow_empty:expr)? $(, sensitive = $sensitive:expr)?
+        $(, required = $required:expr)? $(, help = $help:expr)? $(,)?
+    ) => {
+        $crate::__define_param!(
+            @late, $name, $storage, $crate::parse::setup_repeatable_kv_param,
+            .repeatable()
+            $(, .with_priority($priority))? $(, .in_group($group))?
+            $(, .with_allow_empty($allow_empty))? $(, .with_sensitive($sensitive))?
+            $(, .with_required($required))? $(, .with_help($help))?
+        );
+    };
+}
+
+/// Defines a **repeatable** `key=value` kernel command-line parameter that
+/// records the position of each value.
+///
+/// Almost same as [`define_repeatable_kv_param!`], but the storage must be a
+/// `spin::Mutex<Vec<(usize, T)>>`, where `T` implements
+/// [`crate::parse::ParseParamValue`]. Each value is stored with the index of
+/// its token among all the tokens processed so far (see
+/// [`crate::CmdlineStats::tokens`]), so the values can be ordered against
+/// those of other parameters. The indices increase in the order of the
+/// command line. Each parsed command line overwrites the stored values.
+///
+/// # Examples
+///
+/// ```ignore
+/// static CONSOLES: Mutex<Vec<(usize, String)>> = Mutex::new(Vec::new());
+/// define_indexed_kv_param!("console", CONSOLES);
+/// ```
+#[macro_export]
+macro_rules! define_indexed_kv_param {
+    (
+        $name:expr, $storage:expr
+        $(, priority = $priority:expr)? $(, group = $group:expr)?
+        $(, allow_empty = $allow_empty:expr)? $(, sensitive = $sensitive:expr)?
+        $(, required = $required:expr)? $(, help = $help:expr)? $(,)?
+    ) => {
+        $crate::__define_param!(
+            @indexed, $name, $storage, $crate::parse::setup_indexed_kv_param,
+            .repeatable()
+            $(, .with_priority($priority))? $(, .in_group($group))?
+            $(, .with_allow_empty($allow_empty))? $(, .with_sensitive($sensitive))?
+            $(, .with_required($required))? $(, .with_help($help))?
+        );
     };
 }
 
@@ -145,8 +359,19 @@ macro_rules! define_repeatable_kv_param {
 /// Almost same as [`define_repeatable_kv_param!`], but it is processed earlier in the boot sequence.
 #[macro_export]
 macro_rules! define_repeatable_kv_param_early {
-    ($name:expr, $storage:expr) => {
-        $crate::__define_param!(@early, $name, $storage, $crate::parse::setup_repeatable_kv_param);
+    (
+        $name:expr, $storage:expr
+        $(, priority = $priority:expr)? $(, group = $group:expr)?
+        $(, allow_empty = $allow_empty:expr)? $(, sensitive = $sensitive:expr)?
+        $(, required = $required:expr)? $(, help = $help:expr)? $(,)?
+    ) => {
+        $crate::__define_param!(
+            @early, $name, $storage, $crate::parse::setup_repeatable_kv_param,
+            .repeatable()
+            $(, .with_priority($priority))? $(, .in_group($group))?
+            $(, .with_allow_empty($allow_empty))? $(, .with_sensitive($sensitive))?
+            $(, .with_required($required))? $(, .with_help($help))?
+        );
     };
 }
 
@@ -160,6 +385,12 @@ macro_rules! define_repeatable_kv_param_early {
 /// - `$name`: Flag name (e.g. `"debug"`).
 /// - `$storage`: Storage location for the parsed value. Its type must implement
 ///   [`crate::parse::ParamStorage`].
+/// - `priority = $priority` (optional): Same as in [`define_kv_param!`].
+/// - `group = $group` (optional): Same as in [`define_kv_param!`].
+/// - `allow_empty = $allow_empty` (optional): Same as in [`define_kv_param!`].
+/// - `sensitive = $sensitive` (optional): Same as in [`define_kv_param!`].
+/// - `required = $required` (optional): Same as in [`define_kv_param!`].
+/// - `help = $help` (optional): Same as in [`define_kv_param!`].
 ///
 /// # Parsing
 ///
@@ -176,8 +407,18 @@ macro_rules! define_repeatable_kv_param_early {
 /// ```
 #[macro_export]
 macro_rules! define_flag_param {
-    ($name:expr, $storage:expr) => {
-        $crate::__define_param!(@late, $name, $storage, $crate::parse::setup_flag_param);
+    (
+        $name:expr, $storage:expr
+        $(, priority = $priority:expr)? $(, group = $group:expr)?
+        $(, allow_empty = $allow_empty:expr)? $(, sensitive = $sensitive:expr)?
+        $(, required = $required:expr)? $(, help = $help:expr)? $(,)?
+    ) => {
+        $crate::__define_param!(
+            @late, $name, $storage, $crate::parse::setup_flag_param
+            $(, .with_priority($priority))? $(, .in_group($group))?
+            $(, .with_allow_empty($allow_empty))? $(, .with_sensitive($sensitive))?
+            $(, .with_required($required))? $(, .with_help($help))?
+        );
     };
 }
 
@@ -186,32 +427,174 @@ macro_rules! define_flag_param {
 /// Almost same as [`define_flag_param!`], but it is processed earlier in the boot sequence.
 #[macro_export]
 macro_rules! define_flag_param_early {
-    ($name:expr, $storage:expr) => {
-        $crate::__define_param!(@early, $name, $storage, $crate::parse::setup_flag_param);
+    (
+        $name:expr, $storage:expr
+        $(, priority = $priority:expr)? $(, group = $group:expr)?
+        $(, allow_empty = $allow_empty:expr)? $(, sensitive = $sensitive:expr)?
+        $(, required = $required:expr)? $(, help = $help:expr)? $(,)?
+    ) => {
+        $crate::__define_param!(
+            @early, $name, $storage, $crate::parse::setup_flag_param
+            $(, .with_priority($priority))? $(, .in_group($group))?
+            $(, .with_allow_empty($allow_empty))? $(, .with_sensitive($sensitive))?
+            $(, .with_required($required))? $(, .with_help($help))?
+        );
+    };
+}
+
+/// Defines a **prefix** kernel command-line parameter.
+///
+/// The parameter matches any token whose name starts with `$prefix` (e.g.
+/// `"mydrv."`) and does not exactly match another parameter. This allows a
+/// subsystem to receive all the parameters under its namespace.
+///
+/// # Arguments
+///
+/// - `$prefix`: Name prefix (e.g. `"mydrv."`).
+/// - `$setup_fn`: A function of type
+///   `fn(suffix: &str, value: Option<&str>) -> Result<(), ParamError>`.
+///   It is called once per occurrence in the order of the command line, after
+///   all the other parameters are set up. `suffix` is the rest of the name
+///   after `$prefix`.
+/// - `help = $help` (optional): Same as in [`define_kv_param!`].
+///
+/// # Examples
+///
+/// ```ignore
+/// fn setup_mydrv(suffix: &str, value: Option<&str>) -> Result<(), ParamError> {
+///     // For `mydrv.foo=1`, `suffix` is "foo" and `value` is `Some("1")`.
+///     Ok(())
+/// }
+/// define_prefix_param!("mydrv.", setup_mydrv);
+/// ```
+#[macro_export]
+macro_rules! define_prefix_param {
+    ($prefix:expr, $setup_fn:path $(, help = $help:expr)? $(,)?) => {
+        const _: () = {
+            $crate::submit! {
+                $crate::KernelParam::new_prefixed($prefix, $setup_fn)
+                    .at_location(concat!(file!(), ":", line!()))
+                    $(.with_help($help))?
+            }
+        };
+    };
+}
+
+/// Defines a **module** kernel command-line parameter.
+///
+/// The parameter receives every token named `$module.<key>` (e.g. `e1000.debug=1`
+/// for the module `"e1000"`) that does not exactly match another parameter.
+/// Such tokens are otherwise dropped as unknown module parameters. Unlike
+/// [`define_prefix_param!`], the module name is given without the trailing `.`,
+/// which is required between the module name and the sub-key.
+///
+/// # Arguments
+///
+/// - `$module`: Module name without `.` (e.g. `"e1000"`).
+/// - `$setup_fn`: Same as in [`define_prefix_param!`]. For `e1000.debug=1`,
+///   `suffix` is `"debug"` and `value` is `Some("1")`.
+/// - `help = $help` (optional): Same as in [`define_kv_param!`].
+///
+/// # Examples
+///
+/// ```ignore
+/// fn setup_e1000(suffix: &str, value: Option<&str>) -> Result<(), ParamError> {
+///     Ok(())
+/// }
+/// define_kernel_param_module!("e1000", setup_e1000);
+/// ```
+#[macro_export]
+macro_rules! define_kernel_param_module {
+    ($module:expr, $setup_fn:path $(, help = $help:expr)? $(,)?) => {
+        const _: () = {
+            $crate::submit! {
+                $crate::KernelParam::new_module($module, $setup_fn)
+                    .at_location(concat!(file!(), ":", line!()))
+                    $(.with_help($help))?
+            }
+        };
+    };
+}
+
+/// Registers a validator of the parameters as a whole.
+///
+/// The validator is a function of type `fn() -> Result<(), &'static str>`. It
+/// runs after all the parameters of a command line are set up, and an `Err`
+/// with its reason is reported as a diagnostic naming the validator. See
+/// [`CmdlineValidator`].
+///
+/// # Examples
+///
+/// ```ignore
+/// fn check_mem_range() -> Result<(), &'static str> {
+///     match (MIN_MEM.get(), MAX_MEM.get()) {
+///         (Some(min), Some(max)) if min > max => Err("min_mem is greater than max_mem"),
+///         _ => Ok(()),
+///     }
+/// }
+/// register_cmdline_validator!(check_mem_range);
+/// ```
+#[macro_export]
+macro_rules! register_cmdline_validator {
+    ($validate_fn:path $(,)?) => {
+        const _: () = {
+            $crate::submit! {
+                $crate::CmdlineValidator::new(stringify!($validate_fn), $validate_fn)
+            }
+        };
     };
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __define_param {
-    (@late, $name:expr, $storage:expr, $setup:path) => {
+    (@late, $name:expr, $storage:expr, $setup:path $(, .$method:ident($($arg:expr),*))*) => {
+        $crate::__define_param!(@submit, $name, $storage, $setup, false $(, .$method($($arg),*))*);
+    };
+
+    (@early, $name:expr, $storage:expr, $setup:path $(, .$method:ident($($arg:expr),*))*) => {
+        $crate::__define_param!(@submit, $name, $storage, $setup, true $(, .$method($($arg),*))*);
+    };
+
+    (@indexed, $name:expr, $storage:expr, $setup:path $(, .$method:ident($($arg:expr),*))*) => {
         const _: () = {
-            fn __kparam_setup(occurrences: &[Option<&str>]) {
-                $setup(&$storage, occurrences, $name);
+            fn __kparam_setup(
+                occurrences: &[(usize, Option<&str>)],
+                notes: &mut $crate::parse::ParamNotes,
+            ) -> Result<(), $crate::parse::ParamError> {
+                $setup(&$storage, $name, occurrences, notes)
+            }
+            fn __kparam_storage_addr() -> usize {
+                ::core::ptr::from_ref(&$storage).addr()
             }
             $crate::submit! {
-                $crate::KernelParam::new($name, __kparam_setup, false)
+                $crate::KernelParam::new_indexed($name, __kparam_setup)
+                    .at_location(concat!(file!(), ":", line!()))
+                    .with_storage_addr(__kparam_storage_addr)
+                    $(.$method($($arg),*))*
             }
         };
     };
 
-    (@early, $name:expr, $storage:expr, $setup:path) => {
+    (
+        @submit, $name:expr, $storage:expr, $setup:path, $early:expr
+        $(, .$method:ident($($arg:expr),*))*
+    ) => {
         const _: () = {
-            fn __kparam_setup(occurrences: &[Option<&str>]) {
-                $setup(&$storage, occurrences, $name);
+            fn __kparam_setup(
+                occurrences: &[Option<&str>],
+                notes: &mut $crate::parse::ParamNotes,
+            ) -> Result<(), $crate::parse::ParamError> {
+                $setup(&$storage, $name, occurrences, notes)
+            }
+            fn __kparam_storage_addr() -> usize {
+                ::core::ptr::from_ref(&$storage).addr()
             }
             $crate::submit! {
-                $crate::KernelParam::new($name, __kparam_setup, true)
+                $crate::KernelParam::new($name, __kparam_setup, $early)
+                    .at_location(concat!(file!(), ":", line!()))
+                    .with_storage_addr(__kparam_storage_addr)
+                    $(.$method($($arg),*))*
             }
         };
     };
@@ -5,13 +5,15 @@
 //! This module defines traits for parsing different types of parameters and
 //! provides implementations for common cases (e.g., `FromStr` types, flags).
 
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use core::{
     str::FromStr,
     sync::atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering},
 };
 
-use spin::Once;
+use spin::{Mutex, Once};
+
+use crate::types::NumericBase;
 
 /// Trait for types that can store a parsed parameter value.
 pub trait ParamStorage: Sync + 'static {
@@ -26,6 +28,16 @@ impl<T: Send + Sync + 'static> ParamStorage for Once<T> {
     }
 }
 
+/// Unlike `Once<T>`, which keeps the first value ever stored, this storage is
+/// overwritten each time a value is stored, e.g. when another command line is
+/// parsed with [`crate::KernelCmdline::try_from_cmdline`].
+impl<T: Send + 'static> ParamStorage for Mutex<Option<T>> {
+    type Value = T;
+    fn store_param(&self, value: T) {
+        *self.lock() = Some(value);
+    }
+}
+
 impl ParamStorage for AtomicU8 {
     type Value = u8;
     fn store_param(&self, value: u8) {
@@ -50,7 +62,45 @@ impl ParamStorage for AtomicBool {
 /// Errors while parsing kernel command line parameters.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ParamError {
+    /// The value cannot be parsed into the expected type.
     InvalidValue,
+    /// The parameter requires a value, but none is given.
+    MissingValue,
+}
+
+/// Notes on the value of a parameter, e.g., the parts of the value that are ignored.
+///
+/// The notes are reported as the diagnostics of the command line, along with
+/// the errors of the other parameters. The settings of the command line that
+/// affect how the value is parsed are also passed along with the notes.
+#[derive(Debug, Default)]
+pub struct ParamNotes {
+    messages: Vec<String>,
+    numeric_base: NumericBase,
+}
+
+impl ParamNotes {
+    pub(crate) fn new(numeric_base: NumericBase) -> Self {
+        Self {
+            messages: Vec::new(),
+            numeric_base,
+        }
+    }
+
+    /// Adds a note.
+    pub fn note(&mut self, message: String) {
+        self.messages.push(message);
+    }
+
+    /// Returns the base of the unprefixed numbers set by the `numeric_base`
+    /// parameter of the command line, which is decimal by default.
+    pub fn numeric_base(&self) -> NumericBase {
+        self.numeric_base
+    }
+
+    pub(crate) fn into_messages(self) -> Vec<String> {
+        self.messages
+    }
 }
 
 /// Parses a single-value key-value parameter (e.g., log_level=3).
@@ -59,6 +109,29 @@ pub enum ParamError {
 /// semantics (matching Linux behavior) and passes only the final value.
 pub trait ParseParamValue: Sized {
     fn parse_param(value: &str) -> Result<Self, ParamError>;
+
+    /// Parses the value of the parameter named `name`.
+    ///
+    /// The framework calls this method, so a type can override it to behave
+    /// differently for different parameters. By default, `name` is ignored.
+    fn parse_named_param(name: &'static str, value: &str) -> Result<Self, ParamError> {
+        let _ = name;
+        Self::parse_param(value)
+    }
+
+    /// Parses the value of the parameter named `name`, adding the notes on
+    /// the value to `notes`.
+    ///
+    /// The framework calls this method instead of [`Self::parse_named_param`]
+    /// if it can report the notes. By default, no note is added.
+    fn parse_noted_param(
+        name: &'static str,
+        value: &str,
+        notes: &mut ParamNotes,
+    ) -> Result<Self, ParamError> {
+        let _ = notes;
+        Self::parse_named_param(name, value)
+    }
 }
 
 /// Parses a repeatable key-value parameter (e.g., "console=ttyS0 console=ttyS1").
@@ -66,6 +139,27 @@ pub trait ParseParamValue: Sized {
 /// The framework collects all occurrences and passes the full slice.
 pub trait ParseRepeatableParamValue: Sized {
     fn parse_all(values: &[&str]) -> Result<Self, ParamError>;
+
+    /// Parses all the values of the parameter named `name`.
+    ///
+    /// See [`ParseParamValue::parse_named_param`].
+    fn parse_all_named(name: &'static str, values: &[&str]) -> Result<Self, ParamError> {
+        let _ = name;
+        Self::parse_all(values)
+    }
+
+    /// Parses all the values of the parameter named `name`, adding the notes
+    /// on the values to `notes`.
+    ///
+    /// See [`ParseParamValue::parse_noted_param`].
+    fn parse_all_noted(
+        name: &'static str,
+        values: &[&str],
+        notes: &mut ParamNotes,
+    ) -> Result<Self, ParamError> {
+        let _ = notes;
+        Self::parse_all_named(name, values)
+    }
 }
 
 /// Parses a flag parameter (e.g., "ro", "debug", "nokaslr").
@@ -77,6 +171,14 @@ pub trait ParseFlag: Sized {
     /// If the flag is specified as "flag=v", then `value` is `Some(v)`;
     /// if the flag is bare, then `value` is `None`.
     fn parse_flag(value: Option<&str>) -> Result<Self, ParamError>;
+
+    /// Parses the flag named `name` with an optional value.
+    ///
+    /// See [`ParseParamValue::parse_named_param`].
+    fn parse_named_flag(name: &'static str, value: Option<&str>) -> Result<Self, ParamError> {
+        let _ = name;
+        Self::parse_flag(value)
+    }
 }
 
 /// Any `FromStr` type can be a single-value parameter.
@@ -109,52 +211,89 @@ impl ParseFlag for bool {
 }
 
 #[doc(hidden)]
-pub fn setup_kv_param<S: ParamStorage>(storage: &S, occurrences: &[Option<&str>], name: &str)
+pub fn setup_kv_param<S: ParamStorage>(
+    storage: &S,
+    name: &'static str,
+    occurrences: &[Option<&str>],
+    notes: &mut ParamNotes,
+) -> Result<(), ParamError>
 where
     S::Value: ParseParamValue,
 {
     let Some(last) = occurrences.last() else {
-        return;
+        return Ok(());
     };
-    match last {
-        Some(value) => match S::Value::parse_param(value) {
-            Ok(v) => storage.store_param(v),
-            Err(_) => ostd::warn!("invalid value for kernel parameter '{}'", name),
-        },
-        None => ostd::warn!("kernel parameter '{}' requires a value", name),
-    }
+    let value = last.ok_or(ParamError::MissingValue)?;
+    storage.store_param(S::Value::parse_noted_param(name, value, notes)?);
+    Ok(())
 }
 
+/// The value that discards the preceding values of a repeatable parameter.
+///
+/// For example, `console=ttyS0 console=- console=tty0` gives only `tty0`.
+pub const CLEAR_VALUE: &str = "-";
+
 #[doc(hidden)]
 pub fn setup_repeatable_kv_param<S: ParamStorage>(
     storage: &S,
+    name: &'static str,
     occurrences: &[Option<&str>],
-    name: &str,
-) where
+    notes: &mut ParamNotes,
+) -> Result<(), ParamError>
+where
     S::Value: ParseRepeatableParamValue,
 {
     let values: Vec<&str> = occurrences.iter().filter_map(|val| *val).collect();
     if values.is_empty() {
-        ostd::warn!("repeatable parameter '{}' requires values", name);
-        return;
+        return Err(ParamError::MissingValue);
     }
-    match S::Value::parse_all(&values) {
-        Ok(v) => storage.store_param(v),
-        Err(_) => ostd::warn!("invalid value for kernel parameter '{}'", name),
+    let values = match values.iter().rposition(|val| *val == CLEAR_VALUE) {
+        Some(pos) => &values[pos + 1..],
+        None => &values[..],
+    };
+    storage.store_param(S::Value::parse_all_noted(name, values, notes)?);
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn setup_indexed_kv_param<T: ParseParamValue>(
+    storage: &Mutex<Vec<(usize, T)>>,
+    name: &'static str,
+    occurrences: &[(usize, Option<&str>)],
+    notes: &mut ParamNotes,
+) -> Result<(), ParamError> {
+    let values: Vec<(usize, &str)> = occurrences
+        .iter()
+        .filter_map(|(index, val)| val.map(|val| (*index, val)))
+        .collect();
+    if values.is_empty() {
+        return Err(ParamError::MissingValue);
     }
+    let values = match values.iter().rposition(|(_, val)| *val == CLEAR_VALUE) {
+        Some(pos) => &values[pos + 1..],
+        None => &values[..],
+    };
+    let parsed = values
+        .iter()
+        .map(|(index, val)| T::parse_noted_param(name, val, notes).map(|parsed| (*index, parsed)))
+        .collect::<Result<Vec<_>, _>>()?;
+    *storage.lock() = parsed;
+    Ok(())
 }
 
 #[doc(hidden)]
-pub fn setup_flag_param<S: ParamStorage>(storage: &S, occurrences: &[Option<&str>], name: &str)
+pub fn setup_flag_param<S: ParamStorage>(
+    storage: &S,
+    name: &'static str,
+    occurrences: &[Option<&str>],
+    _notes: &mut ParamNotes,
+) -> Result<(), ParamError>
 where
     S::Value: ParseFlag,
 {
-    let last = match occurrences.last() {
-        Some(val) => *val,
-        None => return,
+    let Some(last) = occurrences.last() else {
+        return Ok(());
     };
-    match S::Value::parse_flag(last) {
-        Ok(v) => storage.store_param(v),
-        Err(_) => ostd::warn!("invalid value for flag '{}'", name),
-    }
+    storage.store_param(S::Value::parse_named_flag(name, *last)?);
+    Ok(())
 }
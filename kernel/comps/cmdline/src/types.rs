@@ -4,11 +4,15 @@
 //!
 //! This module provides Linux-style parsers that are frequently used by kernel
 //! command lines so users of this framework don't need to rewrite them.
+//!
+//! Types that implement [`core::str::FromStr`] need no parser here. For example,
+//! IP addresses can be stored as [`core::net::Ipv4Addr`] and
+//! [`core::net::Ipv6Addr`] directly.
 
-use alloc::vec::Vec;
-use core::num::NonZeroU32;
+use alloc::{ffi::CString, format, string::String, vec::Vec};
+use core::{num::NonZeroU32, ops::RangeInclusive};
 
-use crate::parse::{ParamError, ParseParamValue};
+use crate::parse::{ParamError, ParamNotes, ParseFlag, ParseParamValue, ParseRepeatableParamValue};
 
 /// Linux-style CPU list.
 ///
@@ -208,14 +212,61 @@ fn parse_u32(s: &str) -> Result<u32, ParamError> {
     s.parse::<u32>().map_err(|_| ParamError::InvalidValue)
 }
 
+/// Comma-separated list of signed integer ranges.
+///
+/// Each element is either a single integer `n` or a range `n-m` with `n <= m`,
+/// where both ends may be negative, e.g. `"-5--1,1-5,7"`. A `-` at the start
+/// of an end is its sign; the first `-` after the start is the separator.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedRangeList(pub Vec<RangeInclusive<i64>>);
+
+impl ParseParamValue for SignedRangeList {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        value
+            .split(',')
+            .map(parse_signed_range)
+            .collect::<Result<Vec<_>, _>>()
+            .map(SignedRangeList)
+    }
+}
+
+fn parse_signed_range(s: &str) -> Result<RangeInclusive<i64>, ParamError> {
+    // Skip the first character, which may be the sign of the start.
+    let separator_pos = s
+        .get(1..)
+        .and_then(|rest| rest.find('-'))
+        .map(|pos| pos + 1);
+
+    let (start, end) = match separator_pos {
+        Some(pos) => (parse_i64(&s[..pos])?, parse_i64(&s[pos + 1..])?),
+        None => {
+            let n = parse_i64(s)?;
+            (n, n)
+        }
+    };
+
+    if start > end {
+        return Err(ParamError::InvalidValue);
+    }
+
+    Ok(start..=end)
+}
+
+fn parse_i64(s: &str) -> Result<i64, ParamError> {
+    s.parse::<i64>().map_err(|_| ParamError::InvalidValue)
+}
+
 /// Linux-style metric-suffixed u64 value.
 ///
-/// Supports binary multiples (KiB-style):
-/// - `K` = 1024
-/// - `M` = 1024^2
-/// - `G` = 1024^3
-/// - `T` = 1024^4
-/// - `P` = 1024^5
+/// Supports binary multiples (KiB-style), with or without `iB`:
+/// - `K` or `KiB` = 1024
+/// - `M` or `MiB` = 1024^2
+/// - `G` or `GiB` = 1024^3
+/// - `T` or `TiB` = 1024^4
+/// - `P` or `PiB` = 1024^5
+///
+/// Also supports decimal multiples with `B`, e.g. `MB` = 1000^2, for the
+/// tools that distinguish them. Note that a bare `M` is binary, as in Linux.
 ///
 /// Case-insensitive.
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -223,28 +274,36 @@ pub struct MetricU64(pub u64);
 
 impl ParseParamValue for MetricU64 {
     fn parse_param(value: &str) -> Result<Self, ParamError> {
-        if value.is_empty() {
-            return Err(ParamError::InvalidValue);
-        }
-
-        let (num_part, suf) = match value.chars().last() {
-            Some(c) if c.is_ascii_alphabetic() => (&value[..value.len() - c.len_utf8()], Some(c)),
-            _ => (value, None),
-        };
+        let suffix_pos = value
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(value.len());
+        let (num_part, suffix) = value.split_at(suffix_pos);
 
         if num_part.is_empty() {
             return Err(ParamError::InvalidValue);
         }
 
         let base: u64 = num_part.parse().map_err(|_| ParamError::InvalidValue)?;
-        let mul: u64 = match suf.map(|c| c.to_ascii_uppercase()) {
+        let mul = match suffix.get(1..) {
             None => 1,
-            Some('K') => 1024u64,
-            Some('M') => 1024u64.pow(2),
-            Some('G') => 1024u64.pow(3),
-            Some('T') => 1024u64.pow(4),
-            Some('P') => 1024u64.pow(5),
-            _ => return Err(ParamError::InvalidValue),
+            Some(unit) => {
+                let exp = match suffix.as_bytes()[0].to_ascii_uppercase() {
+                    b'K' => 1,
+                    b'M' => 2,
+                    b'G' => 3,
+                    b'T' => 4,
+                    b'P' => 5,
+                    _ => return Err(ParamError::InvalidValue),
+                };
+                let radix: u64 = if unit.is_empty() || unit.eq_ignore_ascii_case("iB") {
+                    1024
+                } else if unit.eq_ignore_ascii_case("B") {
+                    1000
+                } else {
+                    return Err(ParamError::InvalidValue);
+                };
+                radix.pow(exp)
+            }
         };
 
         base.checked_mul(mul)
@@ -253,151 +312,2055 @@ impl ParseParamValue for MetricU64 {
     }
 }
 
-#[cfg(ktest)]
-mod test {
-    use ostd::prelude::*;
+/// Memory window given by its size and base, e.g. `16M@0x1000000`.
+///
+/// The value is `SIZE@BASE`. Each side is either a [`MetricU64`], e.g. `16M`,
+/// or a hexadecimal integer prefixed by `0x` without a suffix, e.g.
+/// `0x1000000`. This is the form of Linux parameters such as `memmap=` and
+/// `crashkernel=`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SizeAt {
+    /// The size of the window in bytes.
+    pub size: u64,
+    /// The start address of the window.
+    pub base: u64,
+}
 
-    use super::*;
+impl ParseParamValue for SizeAt {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        let (size, base) = value.split_once('@').ok_or(ParamError::InvalidValue)?;
+        Ok(SizeAt {
+            size: parse_memory_size(size)?,
+            base: parse_memory_size(base)?,
+        })
+    }
+}
 
-    #[ktest]
-    fn metric_u64_parse_ok() {
-        assert_eq!(MetricU64::parse_param("0").unwrap(), MetricU64(0));
-        assert_eq!(MetricU64::parse_param("1").unwrap(), MetricU64(1));
+fn parse_memory_size(s: &str) -> Result<u64, ParamError> {
+    let Some(hex_digits) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) else {
+        return MetricU64::parse_param(s).map(|MetricU64(size)| size);
+    };
+    // `from_str_radix` accepts a leading `+`, which is not a digit.
+    if hex_digits.starts_with('+') {
+        return Err(ParamError::InvalidValue);
+    }
+    u64::from_str_radix(hex_digits, 16).map_err(|_| ParamError::InvalidValue)
+}
 
-        assert_eq!(MetricU64::parse_param("1K").unwrap(), MetricU64(1024));
-        assert_eq!(MetricU64::parse_param("2k").unwrap(), MetricU64(2 * 1024));
+/// Integer clamped into `LO..=HI`.
+///
+/// Unlike the types that reject an out-of-range value, e.g. [`Percent`], a
+/// value out of the range is replaced by the nearest bound, e.g. `loglevel=99`
+/// gives `Clamp::<0, 7>(7)`. The replacement is noted in the diagnostics of the
+/// command line. A value that is not an integer is still rejected.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Clamp<const LO: i64, const HI: i64>(pub i64);
 
-        assert_eq!(
-            MetricU64::parse_param("3M").unwrap(),
-            MetricU64(3 * 1024u64.pow(2))
-        );
-        assert_eq!(
-            MetricU64::parse_param("4G").unwrap(),
-            MetricU64(4 * 1024u64.pow(3))
-        );
-        assert_eq!(
-            MetricU64::parse_param("5T").unwrap(),
-            MetricU64(5 * 1024u64.pow(4))
-        );
-        assert_eq!(
-            MetricU64::parse_param("6P").unwrap(),
-            MetricU64(6 * 1024u64.pow(5))
-        );
+impl<const LO: i64, const HI: i64> Clamp<LO, HI> {
+    // Returns the clamped value and whether it has been clamped.
+    fn clamp(value: &str) -> Result<(i64, bool), ParamError> {
+        const { assert!(LO <= HI, "the lower bound is greater than the upper bound") };
+        let int = parse_i64(value)?;
+        let clamped = int.clamp(LO, HI);
+        Ok((clamped, clamped != int))
     }
+}
 
-    #[ktest]
-    fn metric_u64_parse_err() {
-        assert!(MetricU64::parse_param("").is_err());
-        assert!(MetricU64::parse_param("   ").is_err());
-        assert!(MetricU64::parse_param("K").is_err());
-        assert!(MetricU64::parse_param("1KB").is_err());
-        assert!(MetricU64::parse_param("1E").is_err());
-        assert!(MetricU64::parse_param("-1").is_err());
-        assert!(MetricU64::parse_param("1.5G").is_err());
+impl<const LO: i64, const HI: i64> ParseParamValue for Clamp<LO, HI> {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        Self::clamp(value).map(|(int, _)| Clamp(int))
     }
 
-    #[ktest]
-    fn cpu_list_parse_ok_and_contains() {
-        let cl = CpuList::parse_param("1").unwrap();
-        assert_eq!(cl.segments().len(), 1);
-        assert!(cl.contains(1));
-        assert!(!cl.contains(0));
-        assert!(!cl.contains(2));
+    fn parse_noted_param(
+        name: &'static str,
+        value: &str,
+        notes: &mut ParamNotes,
+    ) -> Result<Self, ParamError> {
+        let (int, clamped) = Self::clamp(value)?;
+        if clamped {
+            notes.note(format!(
+                "clamping value '{}' of kernel parameter '{}' to {}",
+                value, name, int
+            ));
+        }
+        Ok(Clamp(int))
+    }
+}
 
-        let cl = CpuList::parse_param("1,2,10-20").unwrap();
-        assert_eq!(cl.segments().len(), 3);
-        assert!(cl.contains(1));
-        assert!(cl.contains(2));
-        assert!(cl.contains(10));
-        assert!(cl.contains(20));
-        assert!(!cl.contains(21));
+/// Percentage between 0 and 100.
+///
+/// The value is an integer optionally followed by `%`, e.g. `"25%"` or `"25"`.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Percent(pub u8);
 
-        // range with stride: 100-110:2 => 100,102,104,106,108,110
-        let cl = CpuList::parse_param("100-110:2").unwrap();
-        assert!(cl.contains(100));
-        assert!(!cl.contains(101));
-        assert!(cl.contains(102));
-        assert!(!cl.contains(103));
-        assert!(cl.contains(110));
+impl ParseParamValue for Percent {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        let digits = value.strip_suffix('%').unwrap_or(value);
+        let percent = parse_u32(digits)?;
+        if percent > 100 {
+            return Err(ParamError::InvalidValue);
+        }
+
+        Ok(Percent(percent as u8))
     }
+}
 
-    #[ktest]
-    fn cpu_list_parse_ok_group_stride() {
-        // 0-9:2/4
-        // windows of 4: [0..3] picks 0,2; [4..7] picks 4,6; [8..11] picks 8,10
-        // intersect with 0..9 => 0,2,4,6,8
-        let cl = CpuList::parse_param("0-9:2/4").unwrap();
-        assert!(cl.contains(0));
-        assert!(!cl.contains(1));
-        assert!(cl.contains(2));
-        assert!(!cl.contains(3));
-        assert!(cl.contains(4));
-        assert!(!cl.contains(5));
-        assert!(cl.contains(6));
-        assert!(!cl.contains(7));
-        assert!(cl.contains(8));
-        assert!(!cl.contains(9));
+/// Ratio between 0 and 1.
+///
+/// The value is a decimal number, e.g. `"0.25"` or `"1"`. NaN, infinities, and
+/// numbers out of `0.0..=1.0` are rejected.
+///
+/// A plain `f64` can also be a parameter value through [`core::str::FromStr`],
+/// but it accepts `nan` and `inf` as well. Note that decimal fractions such as
+/// `0.1` are not exact in `f64`, and that the parsing uses floating-point
+/// instructions in the kernel. Prefer [`Percent`] if integer precision suffices.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Ratio(pub f64);
+
+impl ParseParamValue for Ratio {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        let ratio: f64 = value.parse().map_err(|_| ParamError::InvalidValue)?;
+        // NaN is not in any range.
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(ParamError::InvalidValue);
+        }
+
+        Ok(Ratio(ratio))
     }
+}
 
-    #[ktest]
-    fn cpu_list_parse_err() {
-        assert!(CpuList::parse_param("").is_err());
-        assert!(CpuList::parse_param("   ").is_err());
-        assert!(CpuList::parse_param(",").is_err());
-        assert!(CpuList::parse_param("1,").is_err());
-        assert!(CpuList::parse_param("1,,2").is_err());
-        assert!(CpuList::parse_param("a").is_err());
-        assert!(CpuList::parse_param("1-a").is_err());
-        assert!(CpuList::parse_param("2-1").is_err()); // start > end
-        assert!(CpuList::parse_param("1:0").is_err()); // stride 0
-        assert!(CpuList::parse_param("1:2/0").is_err()); // group 0
-        assert!(CpuList::parse_param("1:").is_err());
-        assert!(CpuList::parse_param("1-/2").is_err());
+/// Memory limit given as a percentage of the total with an absolute cap.
+///
+/// The format is `<percent>%:<cap>`, where `<percent>` is no more than 100
+/// and `<cap>` is a [`MetricU64`] value, e.g. `"50%:256M"`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LimitSpec {
+    percent: u8,
+    cap: u64,
+}
+
+impl LimitSpec {
+    /// Returns the percentage of the total (the `P` in `P%:C`).
+    pub fn percent(&self) -> u8 {
+        self.percent
     }
 
-    #[ktest]
-    fn cpu_list_expand_bounded() {
-        let cl = CpuList::parse_param("10-20:2").unwrap(); // 10,12,14,16,18,20 => 6 elems
-        let v = cl.expand_bounded(6);
-        assert_eq!(v.as_slice(), &[10u32, 12u32, 14u32, 16u32, 18u32, 20u32]);
-        let v = cl.expand_bounded(5);
-        assert_eq!(v.as_slice(), &[10u32, 12u32, 14u32, 16u32, 18u32]);
+    /// Returns the absolute cap in bytes (the `C` in `P%:C`).
+    pub fn cap(&self) -> u64 {
+        self.cap
     }
 
-    #[ktest]
-    fn cpu_list_expand_bounded_large_range_stride_fast() {
-        // 0-4_000_000_000:1024 should not scan the whole range; it should just step by 1024.
-        // We only ask for a few elements to ensure it returns quickly and correctly.
-        let cl = CpuList::parse_param("0-4000000000:1024").unwrap();
-        let v = cl.expand_bounded(4);
-        assert_eq!(v.as_slice(), &[0u32, 1024u32, 2048u32, 3072u32]);
+    /// Calculates the limit for the given total amount in bytes.
+    pub fn limit_of(&self, total: u64) -> u64 {
+        let percent_of_total = (total as u128 * self.percent as u128 / 100) as u64;
+        percent_of_total.min(self.cap)
     }
+}
 
-    #[ktest]
-    fn cpu_list_expand_bounded_group_large_range_fast() {
-        // Grouped selection still shouldn't scan by +1.
-        // 0-4_000_000_000:2/4 => in each group of 4 select offsets 0 and 2.
-        // First few selected: 0,2,4,6,8,10,...
-        let cl = CpuList::parse_param("0-4000000000:2/4").unwrap();
-        let v = cl.expand_bounded(6);
-        assert_eq!(v.as_slice(), &[0u32, 2u32, 4u32, 6u32, 8u32, 10u32]);
+impl ParseParamValue for LimitSpec {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        let Some((percent_part, cap_part)) = value.split_once("%:") else {
+            return Err(ParamError::InvalidValue);
+        };
+
+        let percent = parse_u32(percent_part)?;
+        if percent > 100 {
+            return Err(ParamError::InvalidValue);
+        }
+        let MetricU64(cap) = MetricU64::parse_param(cap_part)?;
+
+        Ok(LimitSpec {
+            percent: percent as u8,
+            cap,
+        })
     }
+}
 
-    #[ktest]
-    fn cpu_list_expand_bounded_group_respects_end_boundary() {
-        // Ensure the last (partial) group is clipped by end.
-        // 0-5:2/4 => groups [0..3] => 0,2; [4..7] => 4,6 but end=5 so only 4.
-        let cl = CpuList::parse_param("0-5:2/4").unwrap();
-        let v = cl.expand_bounded(8);
-        assert_eq!(v.as_slice(), &[0u32, 2u32, 4u32]);
+/// Duration with an optional unit suffix, stored in nanoseconds.
+///
+/// Supported units:
+/// - `ns` = nanoseconds
+/// - `us` = microseconds
+/// - `ms` = milliseconds
+/// - `s` = seconds (the default if no unit is given)
+/// - `m` = minutes
+///
+/// Examples: `"500ms"`, `"3s"`, `"2m"`, `"10"`.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Duration(pub u64);
+
+impl From<Duration> for core::time::Duration {
+    fn from(value: Duration) -> Self {
+        core::time::Duration::from_nanos(value.0)
     }
+}
 
-    #[ktest]
-    fn cpu_list_expand_bounded_group_stride_gt_group() {
-        // stride > group: only within=0 is emitted per group.
-        // 0-20:8/4 => groups start at 0,4,8,12,16,20 => emit 0,4,8,12,16,20
-        let cl = CpuList::parse_param("0-20:8/4").unwrap();
-        let v = cl.expand_bounded(16);
-        assert_eq!(v.as_slice(), &[0u32, 4u32, 8u32, 12u32, 16u32, 20u32]);
+impl ParseParamValue for Duration {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        let unit_start = value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len());
+        let (num_part, unit) = value.split_at(unit_start);
+
+        if num_part.is_empty() {
+            return Err(ParamError::InvalidValue);
+        }
+
+        let base: u64 = num_part.parse().map_err(|_| ParamError::InvalidValue)?;
+        let nanos_per_unit: u64 = match unit {
+            "ns" => 1,
+            "us" => 1_000,
+            "ms" => 1_000_000,
+            "" | "s" => 1_000_000_000,
+            "m" => 60 * 1_000_000_000,
+            _ => return Err(ParamError::InvalidValue),
+        };
+
+        base.checked_mul(nanos_per_unit)
+            .map(Duration)
+            .ok_or(ParamError::InvalidValue)
+    }
+}
+
+/// Ordered fallback chain of absolute paths.
+///
+/// The value is a comma-separated list of absolute paths, e.g.
+/// `"/sbin/init,/etc/init,/bin/sh"`. The consumer is expected to try the paths
+/// in order until one of them works.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PathChain(pub Vec<CString>);
+
+impl ParseParamValue for PathChain {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        value
+            .split(',')
+            .map(|path| {
+                if !path.starts_with('/') {
+                    return Err(ParamError::InvalidValue);
+                }
+                CString::new(path).map_err(|_| ParamError::InvalidValue)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(PathChain)
+    }
+}
+
+/// Clock source that can be chosen on the kernel command line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClockSource {
+    /// Time Stamp Counter (`tsc`).
+    Tsc,
+    /// High Precision Event Timer (`hpet`).
+    Hpet,
+    /// ACPI Power Management Timer (`acpi_pm`).
+    AcpiPm,
+    /// Programmable Interval Timer (`pit`).
+    Pit,
+    /// The tick-based clock (`jiffies`).
+    Jiffies,
+}
+
+impl ParseParamValue for ClockSource {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        match value {
+            "tsc" => Ok(ClockSource::Tsc),
+            "hpet" => Ok(ClockSource::Hpet),
+            "acpi_pm" => Ok(ClockSource::AcpiPm),
+            "pit" => Ok(ClockSource::Pit),
+            "jiffies" => Ok(ClockSource::Jiffies),
+            _ => Err(ParamError::InvalidValue),
+        }
+    }
+}
+
+/// Clock sources in the order of preference.
+///
+/// The value is a comma-separated list of clock sources, e.g. `"tsc,hpet"`.
+/// Unknown and duplicate clock sources are rejected.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClockSourceList(pub Vec<ClockSource>);
+
+impl ParseParamValue for ClockSourceList {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        let mut sources = Vec::new();
+        for part in value.split(',') {
+            let source = ClockSource::parse_param(part)?;
+            if sources.contains(&source) {
+                return Err(ParamError::InvalidValue);
+            }
+            sources.push(source);
+        }
+
+        Ok(ClockSourceList(sources))
+    }
+}
+
+/// Namespace that can be set up for the init process.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Namespace {
+    /// Cgroup namespace (`cgroup`).
+    Cgroup,
+    /// IPC namespace (`ipc`).
+    Ipc,
+    /// Mount namespace (`mount`).
+    Mount,
+    /// Network namespace (`net`).
+    Net,
+    /// PID namespace (`pid`).
+    Pid,
+    /// Time namespace (`time`).
+    Time,
+    /// User namespace (`user`).
+    User,
+    /// UTS namespace (`uts`).
+    Uts,
+}
+
+impl ParseParamValue for Namespace {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        match value {
+            "cgroup" => Ok(Namespace::Cgroup),
+            "ipc" => Ok(Namespace::Ipc),
+            "mount" => Ok(Namespace::Mount),
+            "net" => Ok(Namespace::Net),
+            "pid" => Ok(Namespace::Pid),
+            "time" => Ok(Namespace::Time),
+            "user" => Ok(Namespace::User),
+            "uts" => Ok(Namespace::Uts),
+            _ => Err(ParamError::InvalidValue),
+        }
+    }
+}
+
+/// Set of namespaces.
+///
+/// The value is a comma-separated list of namespaces, e.g. `"net,pid,mount"`.
+/// Unknown namespaces are rejected; repeated ones are allowed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NamespaceSet(u8);
+
+impl NamespaceSet {
+    /// Returns whether the set contains the namespace.
+    pub fn contains(&self, namespace: Namespace) -> bool {
+        self.0 & Self::bit_of(namespace) != 0
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    const fn bit_of(namespace: Namespace) -> u8 {
+        1 << namespace as u8
+    }
+}
+
+impl ParseParamValue for NamespaceSet {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        value
+            .split(',')
+            .try_fold(NamespaceSet::default(), |set, part| {
+                let namespace = Namespace::parse_param(part)?;
+                Ok(NamespaceSet(set.0 | NamespaceSet::bit_of(namespace)))
+            })
+    }
+}
+
+/// Numeric base of the unprefixed numbers of [`HexInt`].
+///
+/// The value is either `"dec"` or `"hex"`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NumericBase {
+    #[default]
+    Dec,
+    Hex,
+}
+
+impl NumericBase {
+    const fn radix(self) -> u32 {
+        match self {
+            NumericBase::Dec => 10,
+            NumericBase::Hex => 16,
+        }
+    }
+}
+
+impl ParseParamValue for NumericBase {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        match value {
+            "dec" => Ok(NumericBase::Dec),
+            "hex" => Ok(NumericBase::Hex),
+            _ => Err(ParamError::InvalidValue),
+        }
+    }
+}
+
+/// Integer that accepts both hexadecimal and decimal notations.
+///
+/// A value prefixed with `0x` or `0X` is parsed as a hexadecimal number;
+/// otherwise, it is parsed as a decimal number. If a [`HexInt`] parameter is
+/// set up by the dispatcher, the unprefixed numbers are instead parsed in the
+/// base set by the `numeric_base` parameter of the same command line (see
+/// [`ParamNotes::numeric_base`]). The base does not apply to a [`HexInt`]
+/// nested in another type, e.g., `CommaList<HexInt<u32>>`.
+///
+/// Examples: `"0xdead"`, `"0XBEEF"`, `"1234"`.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct HexInt<T>(pub T);
+
+macro_rules! impl_parse_hex_int {
+    ($($int:ty),*) => {
+        $(
+            impl ParseParamValue for HexInt<$int> {
+                fn parse_param(value: &str) -> Result<Self, ParamError> {
+                    Self::parse_in_base(value, NumericBase::Dec)
+                }
+
+                fn parse_noted_param(
+                    _name: &'static str,
+                    value: &str,
+                    notes: &mut ParamNotes,
+                ) -> Result<Self, ParamError> {
+                    Self::parse_in_base(value, notes.numeric_base())
+                }
+            }
+
+            impl HexInt<$int> {
+                // Parses `value` in `base` unless it is prefixed with `0x` or `0X`.
+                fn parse_in_base(value: &str, base: NumericBase) -> Result<Self, ParamError> {
+                    let (digits, radix) = match value
+                        .strip_prefix("0x")
+                        .or_else(|| value.strip_prefix("0X"))
+                    {
+                        Some(hex_digits) => (hex_digits, 16),
+                        None => (value, base.radix()),
+                    };
+
+                    // `from_str_radix` accepts a leading `+`, which is not a digit.
+                    if digits.starts_with('+') {
+                        return Err(ParamError::InvalidValue);
+                    }
+
+                    <$int>::from_str_radix(digits, radix)
+                        .map(HexInt)
+                        .map_err(|_| ParamError::InvalidValue)
+                }
+            }
+        )*
+    };
+}
+
+impl_parse_hex_int!(u32, u64, usize);
+
+/// Flag with an optional value, e.g. `debug` or `debug=2`.
+///
+/// A bare flag gives `OptValue(None)`. A value is parsed as `T`. Use it with
+/// [`crate::define_flag_param!`], which accepts the bare form.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OptValue<T>(pub Option<T>);
+
+impl<T: ParseParamValue> ParseFlag for OptValue<T> {
+    fn parse_flag(value: Option<&str>) -> Result<Self, ParamError> {
+        value.map(T::parse_param).transpose().map(OptValue)
+    }
+}
+
+/// Tri-state switch: `on`, `off`, or `auto`.
+///
+/// The values are case-insensitive, and `1`/`0` are also accepted for
+/// `on`/`off`. A bare flag means `on`. Use it with [`crate::define_flag_param!`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TriState {
+    On,
+    Off,
+    Auto,
+}
+
+impl ParseFlag for TriState {
+    fn parse_flag(value: Option<&str>) -> Result<Self, ParamError> {
+        let Some(value) = value else {
+            return Ok(TriState::On);
+        };
+        if value == "1" || value.eq_ignore_ascii_case("on") {
+            Ok(TriState::On)
+        } else if value == "0" || value.eq_ignore_ascii_case("off") {
+            Ok(TriState::Off)
+        } else if value.eq_ignore_ascii_case("auto") {
+            Ok(TriState::Auto)
+        } else {
+            Err(ParamError::InvalidValue)
+        }
+    }
+}
+
+/// Switch of a feature that is enabled unless turned off, e.g. `acpi=off`.
+///
+/// Besides the values accepted by `bool`, `enable` and `disable` are accepted.
+/// A bare flag means enabled, and so does the default value. Use it with
+/// [`crate::define_flag_param!`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Enabled(pub bool);
+
+impl Default for Enabled {
+    fn default() -> Self {
+        Enabled(true)
+    }
+}
+
+impl ParseFlag for Enabled {
+    fn parse_flag(value: Option<&str>) -> Result<Self, ParamError> {
+        match value {
+            Some("enable") => Ok(Enabled(true)),
+            Some("disable") => Ok(Enabled(false)),
+            _ => bool::parse_flag(value).map(Enabled),
+        }
+    }
+}
+
+/// Integer with an optional sign and an optional radix prefix.
+///
+/// After the optional `+` or `-` sign, a value prefixed with `0x`, `0o`, or
+/// `0b` is parsed as a hexadecimal, octal, or binary number, respectively;
+/// otherwise, it is parsed as a decimal number.
+///
+/// Examples: `"-0x10"`, `"0b1010"`, `"+0o17"`, `"42"`.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Radix<T>(pub T);
+
+macro_rules! impl_parse_radix {
+    ($($int:ty),*) => {
+        $(
+            impl ParseParamValue for Radix<$int> {
+                fn parse_param(value: &str) -> Result<Self, ParamError> {
+                    let int = parse_radix_int(value)?;
+                    <$int>::try_from(int)
+                        .map(Radix)
+                        .map_err(|_| ParamError::InvalidValue)
+                }
+            }
+        )*
+    };
+}
+
+impl_parse_radix!(i32, i64, isize, u32, u64, usize);
+
+// Parses an integer with an optional sign and an optional radix prefix.
+fn parse_radix_int(value: &str) -> Result<i128, ParamError> {
+    let (is_negative, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (digits, radix) = match unsigned.get(..2) {
+        Some("0x") => (&unsigned[2..], 16),
+        Some("0o") => (&unsigned[2..], 8),
+        Some("0b") => (&unsigned[2..], 2),
+        _ => (unsigned, 10),
+    };
+
+    // `from_str_radix` accepts a leading sign, which must not be repeated.
+    if !digits.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Err(ParamError::InvalidValue);
+    }
+
+    let magnitude = u64::from_str_radix(digits, radix).map_err(|_| ParamError::InvalidValue)?;
+    if is_negative {
+        Ok(-i128::from(magnitude))
+    } else {
+        Ok(i128::from(magnitude))
+    }
+}
+
+/// Integer that allows `_` as a digit separator, e.g. `"1_048_576"`.
+///
+/// Each `_` must be between two digits.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct IntSep<T>(pub T);
+
+macro_rules! impl_parse_int_sep {
+    ($($int:ty),*) => {
+        $(
+            impl ParseParamValue for IntSep<$int> {
+                fn parse_param(value: &str) -> Result<Self, ParamError> {
+                    if value.starts_with('_') || value.ends_with('_') || value.contains("__") {
+                        return Err(ParamError::InvalidValue);
+                    }
+
+                    let digits: String = value.chars().filter(|c| *c != '_').collect();
+                    // `FromStr` accepts a leading `+`, which is not a digit.
+                    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                        return Err(ParamError::InvalidValue);
+                    }
+
+                    digits
+                        .parse::<$int>()
+                        .map(IntSep)
+                        .map_err(|_| ParamError::InvalidValue)
+                }
+            }
+        )*
+    };
+}
+
+impl_parse_int_sep!(u32, u64, usize);
+
+/// Comma-separated list of items.
+///
+/// Each element is parsed as `T`, e.g. `"5s,10s,30s"` for a
+/// `CommaList<Duration>`. The list is rejected if any element is.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommaList<T>(pub Vec<T>);
+
+impl<T: ParseParamValue> ParseParamValue for CommaList<T> {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        value
+            .split(',')
+            .map(T::parse_param)
+            .collect::<Result<Vec<_>, _>>()
+            .map(CommaList)
+    }
+}
+
+/// Sum of the integers given by a repeatable parameter.
+///
+/// Unlike `Vec<u64>`, which keeps each value, the values are added up, e.g.
+/// `reserve=10 reserve=20` gives `Sum(30)` with
+/// `define_repeatable_kv_param!("reserve", TOTAL)`. If the sum overflows, it
+/// saturates at `u64::MAX`, which is noted in the diagnostics of the command
+/// line.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Sum(pub u64);
+
+impl Sum {
+    // Returns the saturating sum and whether it has saturated.
+    fn add_up(values: &[&str]) -> Result<(u64, bool), ParamError> {
+        values
+            .iter()
+            .try_fold((0u64, false), |(sum, saturated), value| {
+                let (sum, overflowed) = sum.overflowing_add(u64::parse_param(value)?);
+                if overflowed {
+                    Ok((u64::MAX, true))
+                } else {
+                    Ok((sum, saturated))
+                }
+            })
+    }
+}
+
+impl ParseRepeatableParamValue for Sum {
+    fn parse_all(values: &[&str]) -> Result<Self, ParamError> {
+        Self::add_up(values).map(|(sum, _)| Sum(sum))
+    }
+
+    fn parse_all_noted(
+        name: &'static str,
+        values: &[&str],
+        notes: &mut ParamNotes,
+    ) -> Result<Self, ParamError> {
+        let (sum, saturated) = Self::add_up(values)?;
+        if saturated {
+            notes.note(format!(
+                "the sum of kernel parameter '{}' saturates at {}",
+                name,
+                u64::MAX
+            ));
+        }
+        Ok(Sum(sum))
+    }
+}
+
+/// Comma-separated sub-options of the form `<key>[=<value>]`.
+///
+/// Each sub-option is split at its first `=`, e.g. `"ro,size=10%,noexec"` for
+/// `[("ro", None), ("size", Some("10%")), ("noexec", None)]`. The values are
+/// kept as strings for the users to parse. An empty key is rejected.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SubOptions(pub Vec<(String, Option<String>)>);
+
+impl SubOptions {
+    /// Returns the value of the sub-option `key`.
+    ///
+    /// The result is `Some(None)` if the sub-option has no value, and `None`
+    /// if it is absent. If the sub-option appears multiple times, the last one
+    /// wins.
+    pub fn get(&self, key: &str) -> Option<Option<&str>> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(option_key, _)| option_key == key)
+            .map(|(_, value)| value.as_deref())
+    }
+}
+
+impl ParseParamValue for SubOptions {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        value
+            .split(',')
+            .map(|option| {
+                let (key, value) = match option.split_once('=') {
+                    Some((key, value)) => (key, Some(value.into())),
+                    None => (option, None),
+                };
+                if key.is_empty() {
+                    return Err(ParamError::InvalidValue);
+                }
+                Ok((key.into(), value))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(SubOptions)
+    }
+}
+
+/// Colon-separated positional fields.
+///
+/// The value is split at every `:`, and the empty fields are kept, e.g.
+/// `"a:b::d"` gives four fields with an empty third one. This suits the values
+/// like `ip=<client>:<server>:<gateway>:<netmask>:<host>:<device>:<proto>`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ColonFields(pub Vec<String>);
+
+impl ColonFields {
+    /// Returns the `i`-th field, or `None` if there are no more than `i` fields.
+    pub fn field(&self, i: usize) -> Option<&str> {
+        self.0.get(i).map(String::as_str)
+    }
+}
+
+impl ParseParamValue for ColonFields {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        Ok(ColonFields(value.split(':').map(String::from).collect()))
+    }
+}
+
+/// String of at most `MAX` bytes.
+///
+/// A longer value is rejected rather than truncated, e.g. `"abcd"` is a
+/// `BoundedStr<4>` but `"abcde"` is not. The rejection is reported as an
+/// invalid value by the dispatcher.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct BoundedStr<const MAX: usize>(pub String);
+
+impl<const MAX: usize> ParseParamValue for BoundedStr<MAX> {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        if value.len() > MAX {
+            return Err(ParamError::InvalidValue);
+        }
+        Ok(BoundedStr(String::from(value)))
+    }
+}
+
+/// Comma-separated list of exactly `N` integers.
+///
+/// Unlike `CommaList<u64>`, a list with fewer or more integers is rejected,
+/// e.g. `"10,20"` is a `FixedIntArray<2>` but not a `FixedIntArray<3>`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FixedIntArray<const N: usize>(pub [u64; N]);
+
+impl<const N: usize> ParseParamValue for FixedIntArray<N> {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        let mut ints = [0; N];
+        let mut parts = value.split(',');
+        for int in ints.iter_mut() {
+            let part = parts.next().ok_or(ParamError::InvalidValue)?;
+            *int = part.parse().map_err(|_| ParamError::InvalidValue)?;
+        }
+        if parts.next().is_some() {
+            return Err(ParamError::InvalidValue);
+        }
+
+        Ok(FixedIntArray(ints))
+    }
+}
+
+/// Comma-separated list of at most `N` items stored inline.
+///
+/// Unlike `CommaList<T>`, the items are kept in an array instead of a `Vec`,
+/// so no heap memory is allocated, e.g., for a parameter parsed early in the
+/// boot. A list with more than `N` items is rejected. The unused slots hold
+/// `T::default()`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SmallList<T, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+impl<T, const N: usize> SmallList<T, N> {
+    /// Returns the parsed items.
+    pub fn as_slice(&self) -> &[T] {
+        &self.items[..self.len]
+    }
+}
+
+impl<T: ParseParamValue + Copy + Default, const N: usize> ParseParamValue for SmallList<T, N> {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        let mut items = [T::default(); N];
+        let mut len = 0;
+        for part in value.split(',') {
+            let item = items.get_mut(len).ok_or(ParamError::InvalidValue)?;
+            *item = T::parse_param(part)?;
+            len += 1;
+        }
+
+        Ok(SmallList { items, len })
+    }
+}
+
+/// Comma-separated list of items with counts.
+///
+/// Each element has the form `<item>[:<count>]`, where `<item>` is parsed as
+/// `T` and `<count>` is a positive integer that defaults to 1, e.g.
+/// `"io:4,net"` for `[(io, 4), (net, 1)]`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CountedList<T>(pub Vec<(T, u32)>);
+
+impl<T: ParseParamValue> ParseParamValue for CountedList<T> {
+    fn parse_param(value: &str) -> Result<Self, ParamError> {
+        value
+            .split(',')
+            .map(|part| {
+                let (item_part, count) = match part.split_once(':') {
+                    Some((item_part, count_part)) => (item_part, parse_u32(count_part)?),
+                    None => (part, 1),
+                };
+                if count == 0 {
+                    return Err(ParamError::InvalidValue);
+                }
+                Ok((T::parse_param(item_part)?, count))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(CountedList)
+    }
+}
+
+/// Defines a capability mask type that maps names to bit positions.
+///
+/// The defined type wraps a `u64` mask and implements
+/// [`crate::parse::ParseParamValue`]. Its value is a comma-separated list of
+/// capability names, e.g. `"cap_a,cap_c"`, and the bits of all the listed
+/// capabilities are ORed into the mask. Unknown names are ignored, and each of
+/// them is noted in the diagnostics of the command line.
+///
+/// # Examples
+///
+/// ```ignore
+/// kernel_param_capmask! {
+///     /// The features enabled by the `features` parameter.
+///     pub struct Features {
+///         "smap" => 0,
+///         "smep" => 1,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! kernel_param_capmask {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $type_name:ident {
+            $($cap_name:literal => $bit:expr),+ $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        $vis struct $type_name(pub u64);
+
+        impl $type_name {
+            const CAP_BITS: &[(&str, u32)] = &[
+                $(($cap_name, const { assert!($bit < u64::BITS); $bit })),+
+            ];
+        }
+
+        impl $crate::parse::ParseParamValue for $type_name {
+            fn parse_param(value: &str) -> Result<Self, $crate::parse::ParamError> {
+                $crate::types::parse_capmask(value, Self::CAP_BITS).map($type_name)
+            }
+
+            fn parse_noted_param(
+                name: &'static str,
+                value: &str,
+                notes: &mut $crate::parse::ParamNotes,
+            ) -> Result<Self, $crate::parse::ParamError> {
+                $crate::types::parse_noted_capmask(name, value, Self::CAP_BITS, notes)
+                    .map($type_name)
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+pub fn parse_capmask(value: &str, cap_bits: &[(&str, u32)]) -> Result<u64, ParamError> {
+    capmask_of(value, cap_bits, |_| {})
+}
+
+#[doc(hidden)]
+pub fn parse_noted_capmask(
+    name: &str,
+    value: &str,
+    cap_bits: &[(&str, u32)],
+    notes: &mut ParamNotes,
+) -> Result<u64, ParamError> {
+    capmask_of(value, cap_bits, |cap_name| {
+        notes.note(format!(
+            "ignoring unknown capability '{}' of kernel parameter '{}'",
+            cap_name, name
+        ))
+    })
+}
+
+// Calls `on_unknown` with each name that is not in `cap_bits`.
+fn capmask_of(
+    value: &str,
+    cap_bits: &[(&str, u32)],
+    mut on_unknown: impl FnMut(&str),
+) -> Result<u64, ParamError> {
+    if value.is_empty() {
+        return Err(ParamError::InvalidValue);
+    }
+
+    let mut mask = 0u64;
+    for cap_name in value.split(',') {
+        if cap_name.is_empty() {
+            return Err(ParamError::InvalidValue);
+        }
+        match cap_bits.iter().find(|(name, _)| *name == cap_name) {
+            Some((_, bit)) => mask |= 1 << bit,
+            None => on_unknown(cap_name),
+        }
+    }
+
+    Ok(mask)
+}
+
+/// Defines a flags type that maps names to bit masks.
+///
+/// The defined type wraps a `u64` mask and implements
+/// [`crate::parse::ParseParamValue`]. Its value is a comma-separated list of
+/// flag names, e.g. `"a,c"`, and the masks of all the listed flags are ORed.
+/// Unlike [`crate::kernel_param_capmask!`], a flag may map to multiple bits, and an
+/// unknown name rejects the whole value.
+///
+/// # Examples
+///
+/// ```ignore
+/// kernel_param_flags! {
+///     /// The debug features enabled by the `debug` parameter.
+///     pub struct DebugFlags {
+///         "verbose" => 0x1,
+///         "timestamps" => 0x2,
+///         "all" => 0x3,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! kernel_param_flags {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $type_name:ident {
+            $($flag_name:literal => $mask:expr),+ $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        $vis struct $type_name(pub u64);
+
+        impl $type_name {
+            const FLAG_MASKS: &[(&str, u64)] = &[$(($flag_name, $mask)),+];
+        }
+
+        impl $crate::parse::ParseParamValue for $type_name {
+            fn parse_param(value: &str) -> Result<Self, $crate::parse::ParamError> {
+                $crate::types::parse_flags(value, Self::FLAG_MASKS).map($type_name)
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+pub fn parse_flags(value: &str, flag_masks: &[(&str, u64)]) -> Result<u64, ParamError> {
+    value.split(',').try_fold(0u64, |flags, flag_name| {
+        let (_, mask) = flag_masks
+            .iter()
+            .find(|(name, _)| *name == flag_name)
+            .ok_or(ParamError::InvalidValue)?;
+        Ok(flags | mask)
+    })
+}
+
+/// Defines a type whose value is one of a fixed set of options.
+///
+/// The defined type implements [`crate::parse::ParseParamValue`] and stores
+/// the index of the given option in the set, which saves defining an enum
+/// for each such parameter. Values that are not in the set are rejected.
+///
+/// # Examples
+///
+/// ```ignore
+/// kernel_param_one_of! {
+///     /// The preemption model chosen by the `preempt` parameter.
+///     pub struct Preempt = ["none", "lazy", "full"];
+/// }
+/// ```
+#[macro_export]
+macro_rules! kernel_param_one_of {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $type_name:ident = [$($option:literal),+ $(,)?];
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        $vis struct $type_name(usize);
+
+        impl $type_name {
+            /// The allowed options.
+            pub const OPTIONS: &[&str] = &[$($option),+];
+
+            /// Returns the index of the option in [`Self::OPTIONS`].
+            pub fn index(&self) -> usize {
+                self.0
+            }
+
+            /// Returns the option.
+            pub fn as_str(&self) -> &'static str {
+                Self::OPTIONS[self.0]
+            }
+        }
+
+        impl $crate::parse::ParseParamValue for $type_name {
+            fn parse_param(value: &str) -> Result<Self, $crate::parse::ParamError> {
+                Self::OPTIONS
+                    .iter()
+                    .position(|option| *option == value)
+                    .map($type_name)
+                    .ok_or($crate::parse::ParamError::InvalidValue)
+            }
+        }
+    };
+}
+
+/// Defines an enum whose variants are chosen by one or more aliases.
+///
+/// The defined enum implements [`crate::parse::ParseParamValue`]. Each variant
+/// lists the values that select it, and the first one is its canonical name.
+/// Values that are not listed are rejected.
+///
+/// # Examples
+///
+/// ```ignore
+/// kernel_param_aliases! {
+///     /// The scheduler chosen by the `sched` parameter.
+///     pub enum Sched {
+///         Fair = ["cfs", "completely-fair", "fair"],
+///         Fifo = ["fifo"],
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! kernel_param_aliases {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $type_name:ident {
+            $(
+                $(#[$variant_attr:meta])*
+                $variant:ident = [$canonical:literal $(, $alias:literal)* $(,)?]
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        $vis enum $type_name {
+            $($(#[$variant_attr])* $variant),+
+        }
+
+        impl $type_name {
+            /// The accepted values and the variants that they select.
+            pub const ALIASES: &[(&str, Self)] = &[
+                $(($canonical, Self::$variant) $(, ($alias, Self::$variant))*),+
+            ];
+
+            /// Returns the canonical name of the variant.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $canonical),+
+                }
+            }
+        }
+
+        impl $crate::parse::ParseParamValue for $type_name {
+            fn parse_param(value: &str) -> Result<Self, $crate::parse::ParamError> {
+                Self::ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == value)
+                    .map(|(_, variant)| *variant)
+                    .ok_or($crate::parse::ParamError::InvalidValue)
+            }
+        }
+    };
+}
+
+/// Defines a string type whose value must match a glob pattern.
+///
+/// The defined type implements [`crate::parse::ParseParamValue`]. In the
+/// pattern, `*` matches any sequence of characters, `?` matches a single
+/// character, and any other character matches itself. Values that do not
+/// match the whole pattern are rejected.
+///
+/// # Examples
+///
+/// ```ignore
+/// kernel_param_glob! {
+///     /// The SCSI disk chosen by the `swap_disk` parameter.
+///     pub struct SwapDisk = "/dev/sd?";
+/// }
+/// ```
+#[macro_export]
+macro_rules! kernel_param_glob {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $type_name:ident = $pattern:literal;
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug, Eq, PartialEq)]
+        $vis struct $type_name(pub $crate::__String);
+
+        impl $type_name {
+            /// The pattern that the values match.
+            pub const PATTERN: &str = $pattern;
+        }
+
+        impl $crate::parse::ParseParamValue for $type_name {
+            fn parse_param(value: &str) -> Result<Self, $crate::parse::ParamError> {
+                if $crate::types::glob_matches(Self::PATTERN, value) {
+                    Ok($type_name(value.into()))
+                } else {
+                    Err($crate::parse::ParamError::InvalidValue)
+                }
+            }
+        }
+    };
+}
+
+/// Checks whether `value` matches the glob `pattern`.
+///
+/// Only the position after the last `*` is retried on a mismatch, so the
+/// matching takes `O(pattern.len() * value.len())` time at most.
+#[doc(hidden)]
+pub fn glob_matches(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    let (mut p, mut v) = (0, 0);
+    // The position of the last `*` in the pattern and the value position that
+    // the characters after it are being matched from.
+    let mut last_star = None;
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                last_star = Some((p, v));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => {
+                let Some((star_p, star_v)) = last_star else {
+                    return false;
+                };
+                // Let the `*` match one more character.
+                last_star = Some((star_p, star_v + 1));
+                p = star_p + 1;
+                v = star_v + 1;
+            }
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(ktest)]
+mod test {
+    use alloc::vec;
+
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn metric_u64_parse_ok() {
+        assert_eq!(MetricU64::parse_param("0").unwrap(), MetricU64(0));
+        assert_eq!(MetricU64::parse_param("1").unwrap(), MetricU64(1));
+
+        assert_eq!(MetricU64::parse_param("1K").unwrap(), MetricU64(1024));
+        assert_eq!(MetricU64::parse_param("2k").unwrap(), MetricU64(2 * 1024));
+
+        assert_eq!(
+            MetricU64::parse_param("3M").unwrap(),
+            MetricU64(3 * 1024u64.pow(2))
+        );
+        assert_eq!(
+            MetricU64::parse_param("4G").unwrap(),
+            MetricU64(4 * 1024u64.pow(3))
+        );
+        assert_eq!(
+            MetricU64::parse_param("5T").unwrap(),
+            MetricU64(5 * 1024u64.pow(4))
+        );
+        assert_eq!(
+            MetricU64::parse_param("6P").unwrap(),
+            MetricU64(6 * 1024u64.pow(5))
+        );
+    }
+
+    #[ktest]
+    fn size_at_parse() {
+        assert_eq!(
+            SizeAt::parse_param("16M@0x1000000").unwrap(),
+            SizeAt {
+                size: 16 * 1024u64.pow(2),
+                base: 0x1000000,
+            }
+        );
+        assert_eq!(
+            SizeAt::parse_param("0x1000@4G").unwrap(),
+            SizeAt {
+                size: 0x1000,
+                base: 4 * 1024u64.pow(3),
+            }
+        );
+
+        assert!(SizeAt::parse_param("16M").is_err());
+        assert!(SizeAt::parse_param("16M@").is_err());
+        assert!(SizeAt::parse_param("@0x1000000").is_err());
+        assert!(SizeAt::parse_param("16X@0x1000000").is_err());
+        assert!(SizeAt::parse_param("16M@0x+10").is_err());
+        assert!(SizeAt::parse_param("16M@1M@2M").is_err());
+    }
+
+    #[ktest]
+    fn metric_u64_parse_iec_and_si() {
+        assert_eq!(MetricU64::parse_param("1MiB").unwrap(), MetricU64(1048576));
+        assert_eq!(MetricU64::parse_param("1MB").unwrap(), MetricU64(1000000));
+        assert_eq!(MetricU64::parse_param("1M").unwrap(), MetricU64(1048576));
+        assert_eq!(MetricU64::parse_param("2kib").unwrap(), MetricU64(2048));
+        assert_eq!(MetricU64::parse_param("500kb").unwrap(), MetricU64(500000));
+        assert_eq!(
+            MetricU64::parse_param("3TB").unwrap(),
+            MetricU64(3 * 1000u64.pow(4))
+        );
+    }
+
+    #[ktest]
+    fn metric_u64_parse_err() {
+        assert!(MetricU64::parse_param("").is_err());
+        assert!(MetricU64::parse_param("   ").is_err());
+        assert!(MetricU64::parse_param("K").is_err());
+        assert!(MetricU64::parse_param("1KiBB").is_err());
+        assert!(MetricU64::parse_param("1iB").is_err());
+        assert!(MetricU64::parse_param("1Mi").is_err());
+        assert!(MetricU64::parse_param("1MBi").is_err());
+        assert!(MetricU64::parse_param("1M1").is_err());
+        assert!(MetricU64::parse_param("1E").is_err());
+        assert!(MetricU64::parse_param("-1").is_err());
+        assert!(MetricU64::parse_param("1.5G").is_err());
+    }
+
+    #[ktest]
+    fn cpu_list_parse_ok_and_contains() {
+        let cl = CpuList::parse_param("1").unwrap();
+        assert_eq!(cl.segments().len(), 1);
+        assert!(cl.contains(1));
+        assert!(!cl.contains(0));
+        assert!(!cl.contains(2));
+
+        let cl = CpuList::parse_param("1,2,10-20").unwrap();
+        assert_eq!(cl.segments().len(), 3);
+        assert!(cl.contains(1));
+        assert!(cl.contains(2));
+        assert!(cl.contains(10));
+        assert!(cl.contains(20));
+        assert!(!cl.contains(21));
+
+        // range with stride: 100-110:2 => 100,102,104,106,108,110
+        let cl = CpuList::parse_param("100-110:2").unwrap();
+        assert!(cl.contains(100));
+        assert!(!cl.contains(101));
+        assert!(cl.contains(102));
+        assert!(!cl.contains(103));
+        assert!(cl.contains(110));
+    }
+
+    #[ktest]
+    fn cpu_list_parse_ok_group_stride() {
+        // 0-9:2/4
+        // windows of 4: [0..3] picks 0,2; [4..7] picks 4,6; [8..11] picks 8,10
+        // intersect with 0..9 => 0,2,4,6,8
+        let cl = CpuList::parse_param("0-9:2/4").unwrap();
+        assert!(cl.contains(0));
+        assert!(!cl.contains(1));
+        assert!(cl.contains(2));
+        assert!(!cl.contains(3));
+        assert!(cl.contains(4));
+        assert!(!cl.contains(5));
+        assert!(cl.contains(6));
+        assert!(!cl.contains(7));
+        assert!(cl.contains(8));
+        assert!(!cl.contains(9));
+    }
+
+    #[ktest]
+    fn cpu_list_parse_err() {
+        assert!(CpuList::parse_param("").is_err());
+        assert!(CpuList::parse_param("   ").is_err());
+        assert!(CpuList::parse_param(",").is_err());
+        assert!(CpuList::parse_param("1,").is_err());
+        assert!(CpuList::parse_param("1,,2").is_err());
+        assert!(CpuList::parse_param("a").is_err());
+        assert!(CpuList::parse_param("1-a").is_err());
+        assert!(CpuList::parse_param("2-1").is_err()); // start > end
+        assert!(CpuList::parse_param("1:0").is_err()); // stride 0
+        assert!(CpuList::parse_param("1:2/0").is_err()); // group 0
+        assert!(CpuList::parse_param("1:").is_err());
+        assert!(CpuList::parse_param("1-/2").is_err());
+    }
+
+    #[ktest]
+    fn cpu_list_expand_bounded() {
+        let cl = CpuList::parse_param("10-20:2").unwrap(); // 10,12,14,16,18,20 => 6 elems
+        let v = cl.expand_bounded(6);
+        assert_eq!(v.as_slice(), &[10u32, 12u32, 14u32, 16u32, 18u32, 20u32]);
+        let v = cl.expand_bounded(5);
+        assert_eq!(v.as_slice(), &[10u32, 12u32, 14u32, 16u32, 18u32]);
+    }
+
+    #[ktest]
+    fn cpu_list_expand_bounded_large_range_stride_fast() {
+        // 0-4_000_000_000:1024 should not scan the whole range; it should just step by 1024.
+        // We only ask for a few elements to ensure it returns quickly and correctly.
+        let cl = CpuList::parse_param("0-4000000000:1024").unwrap();
+        let v = cl.expand_bounded(4);
+        assert_eq!(v.as_slice(), &[0u32, 1024u32, 2048u32, 3072u32]);
+    }
+
+    #[ktest]
+    fn cpu_list_expand_bounded_group_large_range_fast() {
+        // Grouped selection still shouldn't scan by +1.
+        // 0-4_000_000_000:2/4 => in each group of 4 select offsets 0 and 2.
+        // First few selected: 0,2,4,6,8,10,...
+        let cl = CpuList::parse_param("0-4000000000:2/4").unwrap();
+        let v = cl.expand_bounded(6);
+        assert_eq!(v.as_slice(), &[0u32, 2u32, 4u32, 6u32, 8u32, 10u32]);
+    }
+
+    #[ktest]
+    fn cpu_list_expand_bounded_group_respects_end_boundary() {
+        // Ensure the last (partial) group is clipped by end.
+        // 0-5:2/4 => groups [0..3] => 0,2; [4..7] => 4,6 but end=5 so only 4.
+        let cl = CpuList::parse_param("0-5:2/4").unwrap();
+        let v = cl.expand_bounded(8);
+        assert_eq!(v.as_slice(), &[0u32, 2u32, 4u32]);
+    }
+
+    #[ktest]
+    fn cpu_list_expand_bounded_group_stride_gt_group() {
+        // stride > group: only within=0 is emitted per group.
+        // 0-20:8/4 => groups start at 0,4,8,12,16,20 => emit 0,4,8,12,16,20
+        let cl = CpuList::parse_param("0-20:8/4").unwrap();
+        let v = cl.expand_bounded(16);
+        assert_eq!(v.as_slice(), &[0u32, 4u32, 8u32, 12u32, 16u32, 20u32]);
+    }
+
+    crate::kernel_param_capmask! {
+        struct TestCaps {
+            "cap_a" => 0,
+            "cap_b" => 1,
+            "cap_c" => 5,
+        }
+    }
+
+    #[ktest]
+    fn capmask_parse_ok() {
+        assert_eq!(TestCaps::parse_param("cap_a").unwrap(), TestCaps(0b1));
+        assert_eq!(
+            TestCaps::parse_param("cap_a,cap_c").unwrap(),
+            TestCaps(0b100001)
+        );
+        assert_eq!(
+            TestCaps::parse_param("cap_c,cap_b,cap_c").unwrap(),
+            TestCaps(0b100010)
+        );
+    }
+
+    #[ktest]
+    fn capmask_ignores_unknown_caps() {
+        assert_eq!(TestCaps::parse_param("cap_a,cap_x").unwrap(), TestCaps(0b1));
+        assert_eq!(TestCaps::parse_param("cap_x").unwrap(), TestCaps(0));
+    }
+
+    #[ktest]
+    fn capmask_notes_unknown_caps() {
+        let mut notes = ParamNotes::default();
+        assert_eq!(
+            TestCaps::parse_noted_param("test_caps", "cap_x,cap_a,cap_y", &mut notes).unwrap(),
+            TestCaps(0b1)
+        );
+        assert_eq!(
+            notes.into_messages(),
+            [
+                "ignoring unknown capability 'cap_x' of kernel parameter 'test_caps'",
+                "ignoring unknown capability 'cap_y' of kernel parameter 'test_caps'",
+            ]
+        );
+    }
+
+    #[ktest]
+    fn capmask_parse_err() {
+        assert!(TestCaps::parse_param("").is_err());
+        assert!(TestCaps::parse_param(",").is_err());
+        assert!(TestCaps::parse_param("cap_a,").is_err());
+        assert!(TestCaps::parse_param("cap_a,,cap_b").is_err());
+    }
+
+    crate::kernel_param_flags! {
+        struct TestFlags {
+            "a" => 0x1,
+            "b" => 0x2,
+            "c" => 0x4,
+            "ab" => 0x3,
+        }
+    }
+
+    #[ktest]
+    fn flags_parse_ok() {
+        assert_eq!(TestFlags::parse_param("a,c").unwrap(), TestFlags(0x5));
+        assert_eq!(TestFlags::parse_param("ab,c").unwrap(), TestFlags(0x7));
+        assert_eq!(TestFlags::parse_param("b,b").unwrap(), TestFlags(0x2));
+    }
+
+    #[ktest]
+    fn flags_parse_err() {
+        assert!(TestFlags::parse_param("a,x").is_err());
+        assert!(TestFlags::parse_param("").is_err());
+        assert!(TestFlags::parse_param("a,").is_err());
+    }
+
+    crate::kernel_param_one_of! {
+        struct TestPreempt = ["none", "lazy", "full"];
+    }
+
+    #[ktest]
+    fn one_of_parse_ok() {
+        let preempt = TestPreempt::parse_param("full").unwrap();
+        assert_eq!(preempt.index(), 2);
+        assert_eq!(preempt.as_str(), "full");
+        assert_eq!(TestPreempt::parse_param("none").unwrap().index(), 0);
+    }
+
+    #[ktest]
+    fn one_of_parse_err() {
+        assert!(TestPreempt::parse_param("bogus").is_err());
+        assert!(TestPreempt::parse_param("Full").is_err());
+        assert!(TestPreempt::parse_param("").is_err());
+    }
+
+    crate::kernel_param_aliases! {
+        enum TestSched {
+            Fair = ["cfs", "completely-fair", "fair"],
+            Fifo = ["fifo"],
+        }
+    }
+
+    #[ktest]
+    fn aliases_parse() {
+        assert_eq!(TestSched::parse_param("cfs").unwrap(), TestSched::Fair);
+        assert_eq!(TestSched::parse_param("fair").unwrap(), TestSched::Fair);
+        assert_eq!(
+            TestSched::parse_param("completely-fair").unwrap(),
+            TestSched::Fair
+        );
+        assert_eq!(TestSched::parse_param("fifo").unwrap(), TestSched::Fifo);
+        assert_eq!(TestSched::Fair.as_str(), "cfs");
+
+        assert!(TestSched::parse_param("bogus").is_err());
+        assert!(TestSched::parse_param("CFS").is_err());
+        assert!(TestSched::parse_param("").is_err());
+    }
+
+    crate::kernel_param_glob! {
+        struct TestDisk = "/dev/sd?";
+    }
+
+    #[ktest]
+    fn glob_parse_ok() {
+        assert_eq!(TestDisk::parse_param("/dev/sda").unwrap().0, "/dev/sda");
+        assert!(TestDisk::parse_param("/dev/sdz").is_ok());
+    }
+
+    #[ktest]
+    fn glob_parse_err() {
+        assert!(TestDisk::parse_param("/dev/nvme0").is_err());
+        assert!(TestDisk::parse_param("/dev/sd").is_err());
+        assert!(TestDisk::parse_param("/dev/sda1").is_err());
+    }
+
+    #[ktest]
+    fn glob_stars_match() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("/dev/*", "/dev/nvme0n1"));
+        assert!(glob_matches("*a*b", "xaybab"));
+        assert!(glob_matches("a*?c", "abbc"));
+        assert!(!glob_matches("a*?c", "ac"));
+        assert!(!glob_matches("*a*b", "xayba"));
+        assert!(!glob_matches("?", "ab"));
+        assert!(glob_matches("?", "\u{e9}"));
+    }
+
+    #[ktest]
+    fn duration_parse_ok() {
+        assert_eq!(
+            Duration::parse_param("3s").unwrap(),
+            Duration(3_000_000_000)
+        );
+        assert_eq!(
+            Duration::parse_param("500ms").unwrap(),
+            Duration(500_000_000)
+        );
+        assert_eq!(Duration::parse_param("20us").unwrap(), Duration(20_000));
+        assert_eq!(Duration::parse_param("7ns").unwrap(), Duration(7));
+        assert_eq!(
+            Duration::parse_param("2m").unwrap(),
+            Duration(120_000_000_000)
+        );
+        assert_eq!(
+            Duration::parse_param("10").unwrap(),
+            Duration(10_000_000_000)
+        );
+    }
+
+    #[ktest]
+    fn duration_parse_err() {
+        assert!(Duration::parse_param("").is_err());
+        assert!(Duration::parse_param("s").is_err());
+        assert!(Duration::parse_param("5h").is_err());
+        assert!(Duration::parse_param("5S").is_err());
+        assert!(Duration::parse_param("-1s").is_err());
+        assert!(Duration::parse_param("1.5s").is_err());
+        assert!(Duration::parse_param("18446744073709551615m").is_err());
+    }
+
+    #[ktest]
+    fn path_chain_parse_ok() {
+        let chain = PathChain::parse_param("/sbin/init,/etc/init,/bin/sh").unwrap();
+        assert_eq!(chain.0.len(), 3);
+        assert_eq!(chain.0[0].to_bytes(), b"/sbin/init");
+        assert_eq!(chain.0[1].to_bytes(), b"/etc/init");
+        assert_eq!(chain.0[2].to_bytes(), b"/bin/sh");
+
+        let chain = PathChain::parse_param("/").unwrap();
+        assert_eq!(chain.0.len(), 1);
+    }
+
+    #[ktest]
+    fn path_chain_parse_err() {
+        assert!(PathChain::parse_param("").is_err());
+        assert!(PathChain::parse_param("/sbin/init,bin/sh").is_err());
+        assert!(PathChain::parse_param("/sbin/init,").is_err());
+        assert!(PathChain::parse_param("/sbin/init,,/bin/sh").is_err());
+        assert!(PathChain::parse_param("/sbin/in\0it").is_err());
+    }
+
+    #[ktest]
+    fn clock_source_list_parse_ok() {
+        assert_eq!(
+            ClockSourceList::parse_param("tsc,hpet").unwrap(),
+            ClockSourceList(vec![ClockSource::Tsc, ClockSource::Hpet])
+        );
+        assert_eq!(
+            ClockSourceList::parse_param("acpi_pm").unwrap(),
+            ClockSourceList(vec![ClockSource::AcpiPm])
+        );
+    }
+
+    #[ktest]
+    fn clock_source_list_parse_err() {
+        assert!(ClockSourceList::parse_param("").is_err());
+        assert!(ClockSourceList::parse_param("tsc,bogus").is_err());
+        assert!(ClockSourceList::parse_param("tsc,hpet,tsc").is_err());
+        assert!(ClockSourceList::parse_param("tsc,").is_err());
+        assert!(ClockSourceList::parse_param("TSC").is_err());
+    }
+
+    #[ktest]
+    fn namespace_set_parse_ok() {
+        let set = NamespaceSet::parse_param("net,pid").unwrap();
+        assert!(set.contains(Namespace::Net));
+        assert!(set.contains(Namespace::Pid));
+        assert!(!set.contains(Namespace::Mount));
+
+        let set = NamespaceSet::parse_param("mount,mount").unwrap();
+        assert!(set.contains(Namespace::Mount));
+        assert!(!set.is_empty());
+    }
+
+    #[ktest]
+    fn namespace_set_parse_err() {
+        assert!(NamespaceSet::parse_param("bogus").is_err());
+        assert!(NamespaceSet::parse_param("net,bogus").is_err());
+        assert!(NamespaceSet::parse_param("").is_err());
+        assert!(NamespaceSet::parse_param("net,").is_err());
+        assert!(NamespaceSet::parse_param("NET").is_err());
+    }
+
+    #[ktest]
+    fn opt_value_parse() {
+        assert_eq!(OptValue::<u32>::parse_flag(None).unwrap(), OptValue(None));
+        assert_eq!(
+            OptValue::<u32>::parse_flag(Some("2")).unwrap(),
+            OptValue(Some(2))
+        );
+        assert!(OptValue::<u32>::parse_flag(Some("x")).is_err());
+        assert!(OptValue::<u32>::parse_flag(Some("")).is_err());
+    }
+
+    #[ktest]
+    fn tri_state_parse_ok() {
+        assert_eq!(TriState::parse_flag(None).unwrap(), TriState::On);
+        assert_eq!(TriState::parse_flag(Some("on")).unwrap(), TriState::On);
+        assert_eq!(TriState::parse_flag(Some("1")).unwrap(), TriState::On);
+        assert_eq!(TriState::parse_flag(Some("off")).unwrap(), TriState::Off);
+        assert_eq!(TriState::parse_flag(Some("0")).unwrap(), TriState::Off);
+        assert_eq!(TriState::parse_flag(Some("auto")).unwrap(), TriState::Auto);
+        assert_eq!(TriState::parse_flag(Some("AUTO")).unwrap(), TriState::Auto);
+        assert_eq!(TriState::parse_flag(Some("Off")).unwrap(), TriState::Off);
+    }
+
+    #[ktest]
+    fn tri_state_parse_err() {
+        assert!(TriState::parse_flag(Some("maybe")).is_err());
+        assert!(TriState::parse_flag(Some("2")).is_err());
+        assert!(TriState::parse_flag(Some("")).is_err());
+    }
+
+    #[ktest]
+    fn enabled_parse() {
+        assert_eq!(Enabled::default(), Enabled(true));
+        assert_eq!(Enabled::parse_flag(None).unwrap(), Enabled(true));
+        for value in ["on", "yes", "1", "true", "enable"] {
+            assert_eq!(Enabled::parse_flag(Some(value)).unwrap(), Enabled(true));
+        }
+        for value in ["off", "no", "0", "false", "disable"] {
+            assert_eq!(Enabled::parse_flag(Some(value)).unwrap(), Enabled(false));
+        }
+
+        assert!(Enabled::parse_flag(Some("")).is_err());
+        assert!(Enabled::parse_flag(Some("enabled")).is_err());
+        assert!(Enabled::parse_flag(Some("2")).is_err());
+    }
+
+    #[ktest]
+    fn radix_parse_ok() {
+        assert_eq!(Radix::<i32>::parse_param("-0x10").unwrap(), Radix(-16));
+        assert_eq!(Radix::<u32>::parse_param("0b1010").unwrap(), Radix(10));
+        assert_eq!(Radix::<i64>::parse_param("0o17").unwrap(), Radix(15));
+        assert_eq!(Radix::<usize>::parse_param("42").unwrap(), Radix(42));
+        assert_eq!(Radix::<isize>::parse_param("+42").unwrap(), Radix(42));
+        assert_eq!(Radix::<u64>::parse_param("-0").unwrap(), Radix(0));
+        assert_eq!(
+            Radix::<i64>::parse_param("-0x8000000000000000").unwrap(),
+            Radix(i64::MIN)
+        );
+    }
+
+    #[ktest]
+    fn radix_parse_err() {
+        assert!(Radix::<i32>::parse_param("0x1g").is_err());
+        assert!(Radix::<i32>::parse_param("0b102").is_err());
+        assert!(Radix::<i32>::parse_param("0x").is_err());
+        assert!(Radix::<i32>::parse_param("").is_err());
+        assert!(Radix::<i32>::parse_param("-").is_err());
+        assert!(Radix::<i32>::parse_param("--1").is_err());
+        assert!(Radix::<i32>::parse_param("0x-1").is_err());
+        assert!(Radix::<u32>::parse_param("-1").is_err());
+        assert!(Radix::<i32>::parse_param("0x80000000").is_err());
+        assert!(Radix::<u64>::parse_param("0x10000000000000000").is_err());
+    }
+
+    #[ktest]
+    fn int_sep_parse_ok() {
+        assert_eq!(IntSep::<u32>::parse_param("1_000").unwrap(), IntSep(1000));
+        assert_eq!(IntSep::<u64>::parse_param("0").unwrap(), IntSep(0));
+        assert_eq!(
+            IntSep::<usize>::parse_param("1_048_576").unwrap(),
+            IntSep(1_048_576)
+        );
+    }
+
+    #[ktest]
+    fn int_sep_parse_err() {
+        assert!(IntSep::<u32>::parse_param("1__").is_err());
+        assert!(IntSep::<u32>::parse_param("_1").is_err());
+        assert!(IntSep::<u32>::parse_param("1_").is_err());
+        assert!(IntSep::<u32>::parse_param("1__0").is_err());
+        assert!(IntSep::<u32>::parse_param("").is_err());
+        assert!(IntSep::<u32>::parse_param("_").is_err());
+        assert!(IntSep::<u32>::parse_param("+1_0").is_err());
+        assert!(IntSep::<u32>::parse_param("1_x").is_err());
+        assert!(IntSep::<u32>::parse_param("4_294_967_296").is_err());
+    }
+
+    #[ktest]
+    fn comma_list_parse_ok() {
+        assert_eq!(
+            CommaList::<Duration>::parse_param("5s,10s,30s").unwrap(),
+            CommaList(vec![
+                Duration(5_000_000_000),
+                Duration(10_000_000_000),
+                Duration(30_000_000_000)
+            ])
+        );
+        assert_eq!(
+            CommaList::<Duration>::parse_param("500ms").unwrap(),
+            CommaList(vec![Duration(500_000_000)])
+        );
+    }
+
+    #[ktest]
+    fn comma_list_parse_err() {
+        assert!(CommaList::<Duration>::parse_param("5s,bad").is_err());
+        assert!(CommaList::<Duration>::parse_param("5s,").is_err());
+        assert!(CommaList::<Duration>::parse_param("").is_err());
+    }
+
+    #[ktest]
+    fn signed_range_list_parse_ok() {
+        assert_eq!(
+            SignedRangeList::parse_param("-5--1,1-5").unwrap(),
+            SignedRangeList(vec![-5..=-1, 1..=5])
+        );
+        assert_eq!(
+            SignedRangeList::parse_param("-3,-2-2,4").unwrap(),
+            SignedRangeList(vec![-3..=-3, -2..=2, 4..=4])
+        );
+    }
+
+    #[ktest]
+    fn signed_range_list_parse_err() {
+        assert!(SignedRangeList::parse_param("").is_err());
+        assert!(SignedRangeList::parse_param("5-1").is_err());
+        assert!(SignedRangeList::parse_param("-1--5").is_err());
+        assert!(SignedRangeList::parse_param("1-").is_err());
+        assert!(SignedRangeList::parse_param("--5").is_err());
+        assert!(SignedRangeList::parse_param("-5---1").is_err());
+        assert!(SignedRangeList::parse_param("1-5,").is_err());
+    }
+
+    #[ktest]
+    fn sum_parse_ok() {
+        assert_eq!(Sum::parse_all(&["10", "20"]).unwrap(), Sum(30));
+        assert_eq!(Sum::parse_all(&["7"]).unwrap(), Sum(7));
+        assert_eq!(Sum::parse_all(&[]).unwrap(), Sum(0));
+    }
+
+    #[ktest]
+    fn sum_saturates() {
+        assert_eq!(
+            Sum::parse_all(&["18446744073709551615", "1"]).unwrap(),
+            Sum(u64::MAX)
+        );
+        assert_eq!(
+            Sum::parse_all(&["18446744073709551615", "1", "1"]).unwrap(),
+            Sum(u64::MAX)
+        );
+    }
+
+    #[ktest]
+    fn sum_notes_saturation() {
+        let mut notes = ParamNotes::default();
+        assert_eq!(
+            Sum::parse_all_noted("test_sum", &["10", "20"], &mut notes).unwrap(),
+            Sum(30)
+        );
+        assert!(notes.into_messages().is_empty());
+
+        let mut notes = ParamNotes::default();
+        assert_eq!(
+            Sum::parse_all_noted("test_sum", &["18446744073709551615", "1"], &mut notes).unwrap(),
+            Sum(u64::MAX)
+        );
+        assert_eq!(
+            notes.into_messages(),
+            ["the sum of kernel parameter 'test_sum' saturates at 18446744073709551615"]
+        );
+    }
+
+    #[ktest]
+    fn sum_parse_err() {
+        assert!(Sum::parse_all(&["10", "x"]).is_err());
+        assert!(Sum::parse_all(&["-1"]).is_err());
+        assert!(Sum::parse_all(&["18446744073709551616"]).is_err());
+    }
+
+    #[ktest]
+    fn sub_options_parse_ok() {
+        let options = SubOptions::parse_param("ro,size=10%,noexec,mode=").unwrap();
+        assert_eq!(options.get("ro"), Some(None));
+        assert_eq!(options.get("size"), Some(Some("10%")));
+        assert_eq!(options.get("noexec"), Some(None));
+        assert_eq!(options.get("mode"), Some(Some("")));
+        assert_eq!(options.get("rw"), None);
+
+        let options = SubOptions::parse_param("opt=a=b,opt=c").unwrap();
+        assert_eq!(options.0.len(), 2);
+        assert_eq!(options.get("opt"), Some(Some("c")));
+    }
+
+    #[ktest]
+    fn sub_options_parse_err() {
+        assert!(SubOptions::parse_param("").is_err());
+        assert!(SubOptions::parse_param("ro,,noexec").is_err());
+        assert!(SubOptions::parse_param("=10%").is_err());
+    }
+
+    #[ktest]
+    fn colon_fields_parse() {
+        let fields = ColonFields::parse_param("a:b::d").unwrap();
+        assert_eq!(fields.0, ["a", "b", "", "d"]);
+        assert_eq!(fields.field(2), Some(""));
+        assert_eq!(fields.field(3), Some("d"));
+        assert_eq!(fields.field(4), None);
+
+        assert_eq!(
+            ColonFields::parse_param("::::").unwrap().0,
+            ["", "", "", "", ""]
+        );
+        assert_eq!(ColonFields::parse_param("").unwrap().0, [""]);
+    }
+
+    #[ktest]
+    fn bounded_str_parse() {
+        assert_eq!(BoundedStr::<4>::parse_param("abcd").unwrap().0, "abcd");
+        assert_eq!(BoundedStr::<4>::parse_param("").unwrap().0, "");
+        assert!(BoundedStr::<4>::parse_param("abcde").is_err());
+        // The bound is in bytes, not characters.
+        assert!(BoundedStr::<4>::parse_param("ééé").is_err());
+    }
+
+    #[ktest]
+    fn fixed_int_array_parse_ok() {
+        assert_eq!(
+            FixedIntArray::<2>::parse_param("10,20").unwrap(),
+            FixedIntArray([10, 20])
+        );
+        assert_eq!(
+            FixedIntArray::<1>::parse_param("18446744073709551615").unwrap(),
+            FixedIntArray([u64::MAX])
+        );
+    }
+
+    #[ktest]
+    fn fixed_int_array_parse_err() {
+        assert!(FixedIntArray::<2>::parse_param("10").is_err());
+        assert!(FixedIntArray::<2>::parse_param("10,20,30").is_err());
+        assert!(FixedIntArray::<2>::parse_param("10,x").is_err());
+        assert!(FixedIntArray::<2>::parse_param("10,").is_err());
+        assert!(FixedIntArray::<2>::parse_param("").is_err());
+    }
+
+    #[ktest]
+    fn small_list_parse() {
+        let list = SmallList::<u32, 3>::parse_param("1,2,3").unwrap();
+        assert_eq!(list.as_slice(), [1, 2, 3]);
+        assert_eq!(
+            SmallList::<u32, 3>::parse_param("7").unwrap().as_slice(),
+            [7]
+        );
+
+        assert!(SmallList::<u32, 3>::parse_param("1,2,3,4").is_err());
+        assert!(SmallList::<u32, 3>::parse_param("1,x").is_err());
+        assert!(SmallList::<u32, 3>::parse_param("").is_err());
+        assert!(SmallList::<u32, 0>::parse_param("1").is_err());
+    }
+
+    #[ktest]
+    fn hex_int_parse_ok() {
+        assert_eq!(
+            HexInt::<u32>::parse_param("0xdead").unwrap(),
+            HexInt(0xdead)
+        );
+        assert_eq!(
+            HexInt::<u64>::parse_param("0XBEEF").unwrap(),
+            HexInt(0xbeef)
+        );
+        assert_eq!(HexInt::<usize>::parse_param("1234").unwrap(), HexInt(1234));
+        assert_eq!(
+            HexInt::<u64>::parse_param("0xffffffffffffffff").unwrap(),
+            HexInt(u64::MAX)
+        );
+    }
+
+    #[ktest]
+    fn hex_int_parse_err() {
+        assert!(HexInt::<u32>::parse_param("").is_err());
+        assert!(HexInt::<u32>::parse_param("0x").is_err());
+        assert!(HexInt::<u32>::parse_param("0xzz").is_err());
+        assert!(HexInt::<u32>::parse_param("0x+1").is_err());
+        assert!(HexInt::<u32>::parse_param("+1").is_err());
+        assert!(HexInt::<u32>::parse_param("dead").is_err());
+        assert!(HexInt::<u32>::parse_param("0x100000000").is_err());
+    }
+
+    #[ktest]
+    fn clamp_parse() {
+        assert_eq!(Clamp::<0, 7>::parse_param("99").unwrap(), Clamp(7));
+        assert_eq!(Clamp::<0, 7>::parse_param("-5").unwrap(), Clamp(0));
+        assert_eq!(Clamp::<0, 7>::parse_param("3").unwrap(), Clamp(3));
+        assert_eq!(Clamp::<-10, 10>::parse_param("-10").unwrap(), Clamp(-10));
+        let mut notes = ParamNotes::default();
+        assert_eq!(
+            Clamp::<0, 7>::parse_noted_param("test_level", "8", &mut notes).unwrap(),
+            Clamp(7)
+        );
+        assert_eq!(
+            notes.into_messages(),
+            ["clamping value '8' of kernel parameter 'test_level' to 7"]
+        );
+        assert!(Clamp::<0, 7>::parse_param("abc").is_err());
+        assert!(Clamp::<0, 7>::parse_param("99999999999999999999").is_err());
+    }
+
+    #[ktest]
+    fn percent_parse_ok() {
+        assert_eq!(Percent::parse_param("25%").unwrap(), Percent(25));
+        assert_eq!(Percent::parse_param("100").unwrap(), Percent(100));
+        assert_eq!(Percent::parse_param("0%").unwrap(), Percent(0));
+    }
+
+    #[ktest]
+    fn percent_parse_err() {
+        assert!(Percent::parse_param("110%").is_err());
+        assert!(Percent::parse_param("abc%").is_err());
+        assert!(Percent::parse_param("25%x").is_err());
+        assert!(Percent::parse_param("25%%").is_err());
+        assert!(Percent::parse_param("%").is_err());
+        assert!(Percent::parse_param("").is_err());
+    }
+
+    #[ktest]
+    fn ratio_parse_ok() {
+        assert_eq!(Ratio::parse_param("0.25").unwrap(), Ratio(0.25));
+        assert_eq!(Ratio::parse_param("1.0").unwrap(), Ratio(1.0));
+        assert_eq!(Ratio::parse_param("0").unwrap(), Ratio(0.0));
+        assert_eq!(Ratio::parse_param("5e-1").unwrap(), Ratio(0.5));
+    }
+
+    #[ktest]
+    fn ratio_parse_err() {
+        assert!(Ratio::parse_param("nan").is_err());
+        assert!(Ratio::parse_param("inf").is_err());
+        assert!(Ratio::parse_param("2.0").is_err());
+        assert!(Ratio::parse_param("-0.5").is_err());
+        assert!(Ratio::parse_param("abc").is_err());
+        assert!(Ratio::parse_param("").is_err());
+    }
+
+    #[ktest]
+    fn limit_spec_parse_ok() {
+        let spec = LimitSpec::parse_param("50%:256M").unwrap();
+        assert_eq!(spec.percent(), 50);
+        assert_eq!(spec.cap(), 256 * 1024u64.pow(2));
+
+        let spec = LimitSpec::parse_param("100%:0").unwrap();
+        assert_eq!(spec.percent(), 100);
+        assert_eq!(spec.cap(), 0);
+    }
+
+    #[ktest]
+    fn limit_spec_limit_of() {
+        let spec = LimitSpec::parse_param("50%:256M").unwrap();
+        assert_eq!(spec.limit_of(256 * 1024u64.pow(2)), 128 * 1024u64.pow(2));
+        assert_eq!(spec.limit_of(1024u64.pow(3)), 256 * 1024u64.pow(2));
+        assert_eq!(spec.limit_of(u64::MAX), 256 * 1024u64.pow(2));
+    }
+
+    #[ktest]
+    fn limit_spec_parse_err() {
+        assert!(LimitSpec::parse_param("").is_err());
+        assert!(LimitSpec::parse_param("150%:1G").is_err());
+        assert!(LimitSpec::parse_param("50%").is_err());
+        assert!(LimitSpec::parse_param("50:256M").is_err());
+        assert!(LimitSpec::parse_param("%:256M").is_err());
+        assert!(LimitSpec::parse_param("50%:").is_err());
+        assert!(LimitSpec::parse_param("50%:1E").is_err());
+    }
+
+    #[derive(Debug, Eq, PartialEq)]
+    enum WorkerKind {
+        Io,
+        Net,
+    }
+
+    impl ParseParamValue for WorkerKind {
+        fn parse_param(value: &str) -> Result<Self, ParamError> {
+            match value {
+                "io" => Ok(WorkerKind::Io),
+                "net" => Ok(WorkerKind::Net),
+                _ => Err(ParamError::InvalidValue),
+            }
+        }
+    }
+
+    #[ktest]
+    fn counted_list_parse_ok() {
+        assert_eq!(
+            CountedList::<WorkerKind>::parse_param("io:4,net").unwrap(),
+            CountedList(vec![(WorkerKind::Io, 4), (WorkerKind::Net, 1)])
+        );
+        assert_eq!(
+            CountedList::<WorkerKind>::parse_param("net:2").unwrap(),
+            CountedList(vec![(WorkerKind::Net, 2)])
+        );
+    }
+
+    #[ktest]
+    fn counted_list_parse_err() {
+        assert!(CountedList::<WorkerKind>::parse_param("").is_err());
+        assert!(CountedList::<WorkerKind>::parse_param("io:0").is_err());
+        assert!(CountedList::<WorkerKind>::parse_param("fs:1").is_err());
+        assert!(CountedList::<WorkerKind>::parse_param("io:").is_err());
+        assert!(CountedList::<WorkerKind>::parse_param("io:x").is_err());
+        assert!(CountedList::<WorkerKind>::parse_param("io,").is_err());
+    }
+
+    #[ktest]
+    fn ipv4_addr_parse() {
+        use core::net::Ipv4Addr;
+
+        assert_eq!(
+            Ipv4Addr::parse_param("192.168.1.1").unwrap(),
+            Ipv4Addr::new(192, 168, 1, 1)
+        );
+        assert_eq!(
+            Ipv4Addr::parse_param("255.255.255.0").unwrap(),
+            Ipv4Addr::new(255, 255, 255, 0)
+        );
+        assert!(Ipv4Addr::parse_param("256.0.0.1").is_err());
+        assert!(Ipv4Addr::parse_param("1.2.3").is_err());
+        assert!(Ipv4Addr::parse_param("1.2.3.4.5").is_err());
+        assert!(Ipv4Addr::parse_param("").is_err());
+    }
+
+    #[ktest]
+    fn ipv6_addr_parse() {
+        use core::net::Ipv6Addr;
+
+        assert_eq!(Ipv6Addr::parse_param("::1").unwrap(), Ipv6Addr::LOCALHOST);
+        assert_eq!(
+            Ipv6Addr::parse_param("2001:db8::1").unwrap(),
+            Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)
+        );
+        assert_eq!(
+            Ipv6Addr::parse_param("2001:db8:0:1:2:3:4:5").unwrap(),
+            Ipv6Addr::new(0x2001, 0xdb8, 0, 1, 2, 3, 4, 5)
+        );
+        assert!(Ipv6Addr::parse_param("::1::2").is_err());
+        assert!(Ipv6Addr::parse_param("1:2:3:4:5:6:7:8:9").is_err());
+        assert!(Ipv6Addr::parse_param("12345::").is_err());
     }
 }
@@ -1,43 +1,44 @@
 // SPDX-License-Identifier: MPL-2.0
 
-//! Unimplemented kernel command-line parameters and placeholder handlers.
+//! Unimplemented kernel command-line parameters.
 //!
 //! This module declares kernel parameters that the framework recognizes but does
 //! not implement behavior for yet. Parameters registered here are consumed by
-//! the dispatcher (they are not forwarded to the init process) and a warning
-//! is logged when they are present.
+//! the dispatcher (they are not forwarded to the init process unless declared
+//! as passthrough) and a diagnostic is reported when they are present.
 
 /// Defines kernel command-line parameters that are intentionally left unimplemented.
 ///
-/// Matching tokens are consumed (not forwarded to `init`) and a warning is logged when
-/// such parameters appear.
+/// Matching tokens are consumed (not forwarded to `init`) and a diagnostic is reported
+/// when such parameters appear (see [`crate::KernelCmdline::diagnostics`]).
+///
+/// With a leading `passthrough = true`, matching tokens are still warned about but
+/// are also forwarded to `init` like unknown tokens. This suits parameters that are
+/// meant for userspace tools rather than the kernel.
 ///
 /// # Examples
 ///
 /// ```ignore
 /// define_unimplemented_param!("foo", "bar");
+/// define_unimplemented_param!(passthrough = true, "quiet");
 /// ```
 #[macro_export]
 macro_rules! define_unimplemented_param {
-    ($($name:expr),+ $(,)?) => {
+    (passthrough = $passthrough:expr, $($name:expr),+ $(,)?) => {
         $(
             const _: () = {
-                fn __kparam_setup(occurrences: &[Option<&str>]) {
-                    $crate::setup_unimplemented(occurrences, $name);
-                }
                 $crate::submit! {
-                    $crate::KernelParam::new($name, __kparam_setup, false)
+                    $crate::KernelParam::new_unimplemented($name)
+                        .at_location(concat!(file!(), ":", line!()))
+                        .with_passthrough($passthrough)
                 }
             };
         )+
     };
-}
 
-#[doc(hidden)]
-pub fn setup_unimplemented(occurrences: &[Option<&str>], name: &str) {
-    if !occurrences.is_empty() {
-        ostd::warn!("kernel parameter '{}' is not yet implemented", name);
-    }
+    ($($name:expr),+ $(,)?) => {
+        $crate::define_unimplemented_param!(passthrough = false, $($name),+);
+    };
 }
 
 // Placeholders for recognized but unimplemented kernel command-line parameters.
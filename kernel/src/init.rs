@@ -2,7 +2,7 @@
 
 //! Kernel initialization.
 
-use aster_cmdline::INIT_PROC_ARGS;
+use aster_cmdline::KernelCmdline;
 use component::InitStage;
 use ostd::{cpu::CpuId, util::id_set::Id};
 use spin::once::Once;
@@ -138,10 +138,14 @@ fn first_kthread() {
     print_banner();
 
     INIT_PROCESS.call_once(|| {
-        let karg = INIT_PROC_ARGS.get().unwrap();
-        let init_path = INIT_PATH.get().map(|s| s.as_str());
-        spawn_init_process(init_path, karg.argv().to_vec(), karg.envp().to_vec())
-            .expect("Failed to run the init process")
+        let cmdline = KernelCmdline::get();
+        let karg = cmdline.init_proc_args();
+        spawn_init_process(
+            cmdline.initproc_path(),
+            karg.argv().to_vec(),
+            karg.envp().to_vec(),
+        )
+        .expect("Failed to run the init process")
     });
 }
 
@@ -169,6 +173,3 @@ pub(super) fn on_first_process_startup(ctx: &Context) {
     crate::device::init_in_first_process(ctx).unwrap();
     crate::fs::init_in_first_process(ctx);
 }
-
-static INIT_PATH: Once<String> = Once::new();
-aster_cmdline::define_kv_param!("init", INIT_PATH);
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Kernel command-line tokenization.
+//!
+//! The full parsing of kernel parameters is done outside OSTD. This module
+//! provides the tokenization shared by that parsing and the code that runs
+//! before it.
+
+use core::ops::Range;
+
+use super::boot_info;
+
+/// Splits a kernel command line into tokens.
+///
+/// The tokens are separated by whitespace, except the whitespace protected by
+/// double quotes (`"`) or single quotes (`'`). A quoted region is closed only
+/// by the same quote character that opened it, so a `"` inside `'...'` is
+/// taken literally and vice versa. The quotes are kept in the tokens.
+pub fn split_arg(input: &str) -> impl Iterator<Item = &str> {
+    split_arg_spans(input).map(|(_, arg)| arg)
+}
+
+/// Splits a kernel command line into tokens with their byte ranges in `input`.
+///
+/// The tokens are the same as those of [`split_arg`], and `&input[range]` is
+/// the token yielded with `range`.
+pub fn split_arg_spans(input: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    let mut open_quote = None;
+
+    input
+        .split(move |c: char| {
+            match open_quote {
+                None if c == '"' || c == '\'' => open_quote = Some(c),
+                Some(quote) if c == quote => open_quote = None,
+                _ => (),
+            }
+
+            open_quote.is_none() && c.is_whitespace()
+        })
+        .filter(|arg| !arg.is_empty())
+        .map(move |arg| {
+            // The token is a subslice of `input`.
+            let start = arg.as_ptr() as usize - input.as_ptr() as usize;
+            (start..start + arg.len(), arg)
+        })
+}
+
+/// Checks whether the kernel command line from the bootloader has an entry.
+///
+/// An entry matches a token `flag` or `flag=<value>` before `--`. The names
+/// are compared exactly, without the normalization of hyphens or the
+/// knowledge of the registered parameters. So this function is meant for the
+/// code that runs before the command line is fully parsed.
+pub fn raw_cmdline_contains(flag: &str) -> bool {
+    cmdline_contains(&boot_info().kernel_cmdline, flag)
+}
+
+fn cmdline_contains(cmdline: &str, flag: &str) -> bool {
+    split_arg(cmdline)
+        .take_while(|arg| *arg != "--")
+        .any(|arg| arg.split_once('=').map_or(arg, |(name, _)| name) == flag)
+}
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::prelude::{Vec, ktest};
+
+    #[ktest]
+    fn split_arg_spans_unquoted() {
+        let spans: Vec<_> = split_arg_spans("  ro  init=/bin/sh\tx").collect();
+        assert_eq!(
+            spans,
+            [(2..4, "ro"), (6..18, "init=/bin/sh"), (19..20, "x")]
+        );
+    }
+
+    #[ktest]
+    fn split_arg_spans_quoted() {
+        let input = "a=\"x y\" 'b c' d";
+        let spans: Vec<_> = split_arg_spans(input).collect();
+        assert_eq!(
+            spans,
+            [(0..7, "a=\"x y\""), (8..13, "'b c'"), (14..15, "d")]
+        );
+        assert!(
+            spans
+                .iter()
+                .all(|(range, arg)| &input[range.clone()] == *arg)
+        );
+    }
+
+    #[ktest]
+    fn cmdline_contains_present() {
+        assert!(cmdline_contains("quiet earlycon", "earlycon"));
+        assert!(cmdline_contains("earlycon=uart,io,0x3f8 quiet", "earlycon"));
+        assert!(cmdline_contains("msg=\"a b\" earlycon", "earlycon"));
+    }
+
+    #[ktest]
+    fn cmdline_contains_absent() {
+        assert!(!cmdline_contains("", "earlycon"));
+        assert!(!cmdline_contains("earlycon_x noearlycon", "earlycon"));
+        assert!(!cmdline_contains("msg=\"a earlycon\"", "earlycon"));
+        assert!(!cmdline_contains("quiet -- earlycon", "earlycon"));
+    }
+}
@@ -11,6 +11,7 @@
     expect(dead_code)
 )]
 
+pub mod cmdline;
 pub mod memory_region;
 pub mod smp;
 
@@ -28,6 +29,12 @@ pub struct BootInfo {
     pub bootloader_name: String,
     /// The kernel command line arguments.
     pub kernel_cmdline: String,
+    /// The built-in kernel command line arguments.
+    ///
+    /// They are embedded into the kernel image at build time by specifying the
+    /// `OSTD_BUILTIN_KERNEL_CMDLINE` environment variable. They are meant to be
+    /// appended after [`Self::kernel_cmdline`] to provide default arguments.
+    pub builtin_kernel_cmdline: &'static str,
     /// The initial ramfs raw bytes.
     pub initramfs: Option<&'static [u8]>,
     /// The framebuffer arguments.
@@ -109,6 +116,7 @@ pub(crate) fn init_after_heap() {
     INFO.call_once(|| BootInfo {
         bootloader_name: boot_time_info.bootloader_name.to_string(),
         kernel_cmdline: boot_time_info.kernel_cmdline.to_string(),
+        builtin_kernel_cmdline: option_env!("OSTD_BUILTIN_KERNEL_CMDLINE").unwrap_or(""),
         initramfs: boot_time_info.initramfs,
         framebuffer_arg: boot_time_info.framebuffer_arg,
         memory_regions: boot_time_info.memory_regions.to_vec(),